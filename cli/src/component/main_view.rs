@@ -65,7 +65,6 @@ impl Input for MainView {
             session.stop_all();
             state.terminate();
             state.focus_on(focus::TERMINATION);
-            state.update_state();
         } else if matches!(event, ComponentEvent::StateChanged) {
             self.normal_scene.on_event(event, state);
             self.settings_scene.on_event(event, state);
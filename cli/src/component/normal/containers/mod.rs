@@ -79,7 +79,11 @@ impl<B: Backend> Component<B> for ContainersScene {
         let mut rows = Vec::new();
         for task_id in &self.containers {
             if let Some(task_state) = state.state.containers.get(task_id) {
-                let col_1 = Cow::Borrowed(task_id.as_ref());
+                let col_1 = if task_state.update_available {
+                    Cow::Owned(format!("{} (update available)", task_id))
+                } else {
+                    Cow::Borrowed(task_id.as_ref())
+                };
                 let mut col_2 = Cow::Borrowed("-");
                 let mut col_3 = Cow::Borrowed("-");
                 let mut col_4 = Cow::Owned(task_state.status.progress().unwrap_or_else(|| "Inactive".to_string()));
@@ -121,6 +125,7 @@ fn containers() -> Vec<TaskId> {
     vec![
         images::Tor::id(),
         images::TariBaseNode::id(),
+        images::TariWallet::id(),
         images::TariSha3Miner::id(),
         images::MmProxy::id(),
         images::XMRig::id(),
@@ -24,6 +24,7 @@
 mod base_node;
 pub mod containers;
 mod mining;
+mod wallet;
 
 use mining::MiningPanel;
 use ratatui::{
@@ -33,7 +34,7 @@ use ratatui::{
 
 use crate::{
     component::{
-        normal::{base_node::BaseNodeWidget, containers::ContainersScene},
+        normal::{base_node::BaseNodeWidget, containers::ContainersScene, wallet::WalletWidget},
         Component, ComponentEvent, Frame, Input,
     },
     state::AppState,
@@ -42,6 +43,7 @@ use crate::{
 pub struct NormalScene {
     mining_panel: MiningPanel,
     base_node_widget: BaseNodeWidget,
+    wallet_widget: WalletWidget,
     containers_scene: ContainersScene,
 }
 
@@ -50,6 +52,7 @@ impl NormalScene {
         Self {
             mining_panel: MiningPanel::new(),
             base_node_widget: BaseNodeWidget::new(),
+            wallet_widget: WalletWidget::new(),
             containers_scene: ContainersScene::new(),
         }
     }
@@ -60,6 +63,7 @@ impl Input for NormalScene {
 
     fn on_event(&mut self, event: ComponentEvent, state: &mut AppState) -> Option<Self::Output> {
         self.base_node_widget.on_event(event, state);
+        self.wallet_widget.on_event(event, state);
         self.mining_panel.on_event(event, state);
         None
     }
@@ -73,6 +77,7 @@ impl<B: Backend> Component<B> for NormalScene {
         let panel_constraints = [
             Constraint::Length(15), // miners
             Constraint::Length(10), // base node
+            Constraint::Length(5),  // wallet
             Constraint::Min(0),
         ];
 
@@ -89,5 +94,6 @@ impl<B: Backend> Component<B> for NormalScene {
         self.containers_scene.draw(f, windows[1], state);
         self.mining_panel.draw(f, panels[0], state);
         self.base_node_widget.draw(f, panels[1], state);
+        self.wallet_widget.draw(f, panels[2], state);
     }
 }
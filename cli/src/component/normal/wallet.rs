@@ -0,0 +1,114 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    prelude::*,
+    widgets::Padding,
+};
+use tari_sdm::ids::ManagedTask;
+use tari_sdm_launchpad::resources::images;
+
+use crate::{
+    component::{
+        elements::block_with_title,
+        widgets::status_line::{StatusLine, StatusReportGetter},
+        Component, ComponentEvent,
+        ComponentEvent::KeyEvent,
+        Frame, Input,
+    },
+    state::AppState,
+};
+
+#[derive(Default)]
+struct WalletStatus {}
+
+impl WalletStatus {
+    /// Returns the balance of the wallet. Only called if the wallet is connected.
+    fn get_balance(&self, state: &AppState) -> Text {
+        let wallet = &state.state.wallet;
+        let available = wallet
+            .balance
+            .as_ref()
+            .map(|balance| balance.available.to_string())
+            .unwrap_or_else(|| "-".into());
+        let pending = wallet
+            .balance
+            .as_ref()
+            .map(|balance| balance.pending_incoming.to_string())
+            .unwrap_or_else(|| "-".into());
+        let top_line = Line::from(format!("Balance: {available}   Pending: {pending}"));
+        Text::from(vec![top_line])
+    }
+}
+
+impl StatusReportGetter for WalletStatus {
+    fn get_status(&self, state: &AppState) -> Text {
+        if state.state.wallet.active {
+            self.get_balance(state)
+        } else if state.state.config.session.is_wallet_active() {
+            state
+                .state
+                .containers
+                .get(&images::TariWallet::id())
+                .map(|task| task.status.to_string())
+                .unwrap_or_else(|| "Starting".into())
+                .into()
+        } else {
+            "Not running".into()
+        }
+    }
+}
+
+pub struct WalletWidget {
+    status: StatusLine<WalletStatus>,
+}
+
+impl WalletWidget {
+    pub fn new() -> Self {
+        Self {
+            status: StatusLine::new(WalletStatus::default()),
+        }
+    }
+
+    pub fn toggle_wallet(state: &mut AppState) {
+        let session = &mut state.state.config.session;
+        session.wallet_layer_active = !session.wallet_layer_active;
+        state.update_state();
+    }
+}
+
+impl Input for WalletWidget {
+    type Output = ();
+
+    fn on_event(&mut self, event: ComponentEvent, state: &mut AppState) -> Option<Self::Output> {
+        if let KeyEvent(key) = event {
+            if key.code == KeyCode::Char('w') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                Self::toggle_wallet(state);
+                return Some(());
+            }
+        }
+        None
+    }
+}
+
+impl<B: Backend> Component<B> for WalletWidget {
+    type State = AppState;
+
+    fn draw(&self, f: &mut Frame<B>, rect: Rect, state: &Self::State) {
+        let wallet_active = state.state.config.session.is_wallet_active();
+        let block = block_with_title(Some("Wallet [Ctrl-W]"), wallet_active).padding(Padding::new(1, 1, 1, 1));
+        let inner_rect = block.inner(rect);
+        f.render_widget(block, rect);
+
+        let constraints = [Constraint::Min(15)];
+        let h_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(constraints)
+            .split(inner_rect);
+
+        self.status.draw(f, h_chunks[0], state);
+    }
+}
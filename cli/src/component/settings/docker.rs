@@ -21,10 +21,12 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
+use log::warn;
 use ratatui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
 };
+use tari_launchpad_protocol::settings::LaunchpadSettings;
 
 use crate::{
     component::{
@@ -59,12 +61,41 @@ impl DockerSettings {
             statuses_sep: Separator::new("Image Statuses", []),
         }
     }
+
+    pub fn check_for_updated_settings(&mut self, state: &mut AppState) {
+        let mut should_write = false;
+        if let Some(LaunchpadSettings { saved_settings, .. }) = &mut state.state.config.settings {
+            if let Some(v) = self.docker_tag.fetch_new_value() {
+                saved_settings.set_tag(v);
+                should_write = true;
+            }
+            if let Some(v) = self.docker_registry.fetch_new_value() {
+                saved_settings.set_registry(v);
+                should_write = true;
+            }
+        } else {
+            warn!("The app state does not have a settings instance configured, so we cannot update the saved settings");
+        }
+        if should_write {
+            state.update_settings();
+        }
+    }
 }
 
 impl Input for DockerSettings {
     type Output = ();
 
     fn on_event(&mut self, event: ComponentEvent, state: &mut AppState) -> Option<Self::Output> {
+        if let ComponentEvent::StateChanged = event {
+            if let Some(settings) = &state.state.config.settings {
+                let saved_settings = &settings.saved_settings;
+                self.docker_tag.set(saved_settings.tag.clone().unwrap_or_default());
+                self.docker_registry
+                    .set(saved_settings.registry.clone().unwrap_or_default());
+            }
+            return None;
+        }
+
         if state.focus_on == DOCKER_SETTINGS {
             match event.pass() {
                 Pass::Up | Pass::Leave => {
@@ -104,6 +135,7 @@ impl Input for DockerSettings {
         } else {
             //
         }
+        self.check_for_updated_settings(state);
         None
     }
 }
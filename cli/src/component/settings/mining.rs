@@ -44,6 +44,7 @@ use crate::{
 pub static MINING_SETTINGS: Focus = focus_id!();
 static MONERO_ADDRESS: Focus = focus_id!();
 static SHA_THREADS: Focus = focus_id!();
+static LEAVE_RUNNING: Focus = focus_id!();
 static MONERO_URL: Focus = focus_id!();
 static WALLET_PAYMENT_ADDRESS: Focus = focus_id!();
 
@@ -52,6 +53,7 @@ pub struct MiningSettings {
     monero_address: LabeledInput,
     monero_url: LabeledInput,
     sha_threads: LabeledInput<usize>,
+    leave_running: LabeledInput<bool>,
     wallet_payment_address: LabeledInput,
 }
 
@@ -62,6 +64,7 @@ impl MiningSettings {
             monero_address: LabeledInput::new("Monero mining address", MONERO_ADDRESS),
             monero_url: LabeledInput::new("Monero node URL", MONERO_URL),
             sha_threads: LabeledInput::new_with_value("SHA3 threads", SHA_THREADS, 2),
+            leave_running: LabeledInput::new_with_value("Leave running on exit (true/false)", LEAVE_RUNNING, false),
             wallet_payment_address: LabeledInput::new("Wallet payment address", WALLET_PAYMENT_ADDRESS),
         }
     }
@@ -81,6 +84,10 @@ impl MiningSettings {
                 saved_settings.set_num_mining_threads(*v);
                 should_write = true;
             }
+            if let Some(v) = self.leave_running.fetch_new_value() {
+                saved_settings.leave_running = *v;
+                should_write = true;
+            }
             if let Some(v) = self.wallet_payment_address.fetch_new_value() {
                 saved_settings.set_wallet_payment_address(v);
                 should_write = true;
@@ -104,6 +111,7 @@ impl Input for MiningSettings {
                     let value = conf.monero_mining_address.clone();
                     self.monero_address.set(value);
                 }
+                self.leave_running.set(settings.saved_settings.leave_running);
                 if let Some(conf) = &settings.saved_settings.mm_proxy {
                     let value = conf.monerod_url.clone();
                     self.monero_url.set(value);
@@ -150,12 +158,28 @@ impl Input for MiningSettings {
                     state.focus_on(MONERO_ADDRESS);
                 },
                 Pass::Down if released => {
-                    state.focus_on(MONERO_URL);
+                    state.focus_on(LEAVE_RUNNING);
                 },
                 _ => {
                     self.sha_threads.on_event(event, state);
                 },
             }
+        } else if state.focus_on == LEAVE_RUNNING {
+            let released = self.leave_running.is_released();
+            match event.pass() {
+                Pass::Leave if released => {
+                    state.focus_on(MINING_SETTINGS);
+                },
+                Pass::Up if released => {
+                    state.focus_on(SHA_THREADS);
+                },
+                Pass::Down if released => {
+                    state.focus_on(MONERO_URL);
+                },
+                _ => {
+                    self.leave_running.on_event(event, state);
+                },
+            }
         } else if state.focus_on == MONERO_URL {
             let released = self.monero_url.is_released();
             match event.pass() {
@@ -163,7 +187,7 @@ impl Input for MiningSettings {
                     state.focus_on(MINING_SETTINGS);
                 },
                 Pass::Up if released => {
-                    state.focus_on(SHA_THREADS);
+                    state.focus_on(LEAVE_RUNNING);
                 },
                 Pass::Down if released => {
                     state.focus_on(WALLET_PAYMENT_ADDRESS);
@@ -209,6 +233,7 @@ impl<B: Backend> Component<B> for MiningSettings {
             Constraint::Length(3),
             Constraint::Length(3),
             Constraint::Length(3),
+            Constraint::Length(3),
             Constraint::Min(0),
         ];
         let chunks = Layout::default()
@@ -220,7 +245,8 @@ impl<B: Backend> Component<B> for MiningSettings {
         self.expert_sep.draw(f, chunks[0], state);
         self.monero_address.draw(f, chunks[1], state);
         self.sha_threads.draw(f, chunks[2], state);
-        self.monero_url.draw(f, chunks[3], state);
-        self.wallet_payment_address.draw(f, chunks[4], state);
+        self.leave_running.draw(f, chunks[3], state);
+        self.monero_url.draw(f, chunks[4], state);
+        self.wallet_payment_address.draw(f, chunks[5], state);
     }
 }
@@ -178,6 +178,9 @@ impl Do<Reaction> for Dashboard {
                     state.state.apply(delta);
                 }
             },
+            Reaction::TransferResult(result) => {
+                log::info!("The transfer is finished: {:?}", result);
+            },
         }
         // Reporting about the state has changed - this gets triggered for every event, so isn't very efficient.
         if let Some(state) = self.state.as_mut() {
@@ -0,0 +1,29 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! The commands that move the images to a host without registry access.
+
+use anyhow::Error;
+use tari_launchpad_protocol::launchpad::LaunchpadAction;
+#[cfg(not(feature = "sim"))]
+use tari_sdm_launchpad::bus::LaunchpadBus;
+#[cfg(feature = "sim")]
+use tari_sim_launchpad::bus::LaunchpadBus;
+
+/// Exports or imports the images without starting the bus, so the containers left running are not touched.
+pub async fn transfer_images(action: LaunchpadAction) -> Result<(), Error> {
+    match action {
+        LaunchpadAction::ExportImages(dir) => {
+            LaunchpadBus::export_images(&dir).await?;
+            println!("The images are exported to {}", dir.display());
+        },
+        LaunchpadAction::ImportImages(dir) => {
+            LaunchpadBus::import_images(&dir).await?;
+            println!("The images are imported from {}", dir.display());
+        },
+        other => {
+            return Err(Error::msg(format!("{:?} doesn't transfer the images", other)));
+        },
+    }
+    Ok(())
+}
@@ -23,6 +23,7 @@
 
 mod dashboard;
 mod events;
+pub mod images;
 mod state;
 pub mod supervisor;
 
@@ -23,13 +23,32 @@
 
 use std::env;
 
-use anyhow::{Context, Error};
+use anyhow::{anyhow, Context, Error};
 use tact::Actor;
-use tari_lp_cli::supervisor::Supervisor;
+use tari_launchpad_protocol::launchpad::LaunchpadAction;
+use tari_lp_cli::{images::transfer_images, supervisor::Supervisor};
 use tari_sdm_assets::configurator::Configurator;
 
+const USAGE: &str = "Usage: tari-lp-cli [export-images <dir> | import-images <dir>]";
+
 #[tokio::main]
 async fn main() -> Result<(), Error> {
+    // The paths are relative to the directory the command is called from
+    let cwd = env::current_dir()?;
+    let mut args = env::args().skip(1);
+    let command = match args.next().as_deref() {
+        None => None,
+        Some("export-images") => {
+            let dir = args.next().ok_or_else(|| anyhow!(USAGE))?;
+            Some(LaunchpadAction::ExportImages(cwd.join(dir)))
+        },
+        Some("import-images") => {
+            let dir = args.next().ok_or_else(|| anyhow!(USAGE))?;
+            Some(LaunchpadAction::ImportImages(cwd.join(dir)))
+        },
+        Some(_) => return Err(anyhow!(USAGE)),
+    };
+
     let mut configurator = Configurator::init()?;
     configurator.init_configuration(false).await?;
 
@@ -38,6 +57,10 @@ async fn main() -> Result<(), Error> {
 
     log4rs::init_file("config/log4rs-cli.yml", Default::default()).context("Can't read a logs configuration file")?;
 
+    if let Some(action) = command {
+        return transfer_images(action).await;
+    }
+
     let supervisor = Supervisor::default();
     let mut addr = supervisor.start();
     addr.join().await?;
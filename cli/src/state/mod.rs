@@ -28,12 +28,16 @@ use std::collections::VecDeque;
 use anyhow::Error;
 pub use focus::Focus;
 use tari_launchpad_protocol::launchpad::{Action, LaunchpadAction, LaunchpadState};
+#[cfg(not(feature = "sim"))]
 use tari_sdm_launchpad::bus::BusTx;
+#[cfg(feature = "sim")]
+use tari_sim_launchpad::bus::BusTx;
 
 pub enum AppEvent {
     SetFocus(Focus),
     SettingsChanged,
     UpdateState,
+    Shutdown,
 }
 
 pub struct AppState {
@@ -56,6 +60,10 @@ impl AppState {
     }
 
     pub fn is_terminated(&mut self) -> bool {
+        // The containers are left running by the request of the user
+        if self.terminate && self.state.detached {
+            return true;
+        }
         let has_active_task = self
             .state
             .containers
@@ -72,6 +80,8 @@ impl AppState {
 
     pub fn terminate(&mut self) {
         self.terminate = true;
+        let event = AppEvent::Shutdown;
+        self.events_queue.push_front(event);
     }
 
     pub fn update_state(&mut self) {
@@ -123,8 +133,49 @@ impl AppState {
                     let action = Action::Action(LaunchpadAction::SaveSettings(settings));
                     self.bus_tx.send(action)?;
                 },
+                AppEvent::Shutdown => {
+                    let action = Action::Action(LaunchpadAction::Shutdown);
+                    self.bus_tx.send(action)?;
+                },
             }
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use tari_launchpad_protocol::{
+        container::{TaskState, TaskStatus},
+        launchpad::LaunchpadDelta,
+        settings::LaunchpadSettings,
+    };
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn quit_leaving_containers_running() {
+        let (bus_tx, mut bus_rx) = mpsc::unbounded_channel();
+        let mut state = LaunchpadState::default();
+        let mut settings = LaunchpadSettings::default();
+        settings.saved_settings.leave_running = true;
+        state.config.settings = Some(settings);
+        let mut task = TaskState::new(false);
+        task.status = TaskStatus::Active;
+        state.containers.insert("minotari_node".into(), task);
+        let mut app = AppState::new(bus_tx, state);
+
+        app.terminate();
+        app.process_events();
+        assert!(matches!(
+            bus_rx.try_recv(),
+            Ok(Action::Action(LaunchpadAction::Shutdown))
+        ));
+        // The container is still running until the backend detaches from it
+        assert!(!app.is_terminated());
+
+        app.state.apply(LaunchpadDelta::Detached);
+        assert!(app.is_terminated());
+    }
+}
@@ -23,7 +23,7 @@
 
 use std::fmt;
 
-use byte_unit::Byte;
+use byte_unit::{Byte, UnitType};
 use chrono::NaiveDateTime;
 use derive_more::{Display, From, Into};
 use serde::{Deserialize, Serialize};
@@ -53,6 +53,8 @@ const FAILS_LIMIT: usize = 10;
 
 const STATS_LIMIT: usize = 30;
 
+const RESTARTS_LIMIT: usize = 10;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogRecord {
     pub datetime: NaiveDateTime,
@@ -60,6 +62,17 @@ pub struct LogRecord {
     pub message: String,
 }
 
+/// An automatic restart of a container that exited unexpectedly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartRecord {
+    pub datetime: NaiveDateTime,
+    /// The number of the restart in a row.
+    pub attempt: u32,
+    /// The delay before the restart in seconds.
+    pub delay: u64,
+    pub exit_code: Option<i64>,
+}
+
 #[derive(Debug, Clone, AsRefStr, Serialize_repr, Deserialize_repr)]
 #[repr(u16)]
 pub enum LogLevel {
@@ -76,7 +89,23 @@ pub struct TaskState {
     pub tail: Frame<LogRecord>,
     pub fails: Frame<ErrorRecord>,
     pub stats: Frame<StatsData>,
+    /// The recent restarts of the crashed container.
+    #[serde(default = "restarts_frame")]
+    pub restarts: Frame<RestartRecord>,
     pub permanent: bool,
+    /// The registry has a newer image than the local one.
+    #[serde(default)]
+    pub update_available: bool,
+    /// The progress of pulling the image ahead of time.
+    #[serde(default)]
+    pub pull: Option<TaskProgress>,
+    /// The fields of the spec of the container that were changed by the last config and required a restart.
+    #[serde(default)]
+    pub changed_fields: Vec<String>,
+}
+
+fn restarts_frame() -> Frame<RestartRecord> {
+    Frame::new(RESTARTS_LIMIT)
 }
 
 impl TaskState {
@@ -86,7 +115,11 @@ impl TaskState {
             tail: Frame::new(TAIL_LIMIT),
             fails: Frame::new(FAILS_LIMIT),
             stats: Frame::new(STATS_LIMIT),
+            restarts: restarts_frame(),
             permanent,
+            update_available: false,
+            pull: None,
+            changed_fields: Vec::new(),
         }
     }
 
@@ -104,6 +137,18 @@ impl TaskState {
             TaskDelta::StatsRecord(record) => {
                 self.stats.push(record);
             },
+            TaskDelta::Restart(record) => {
+                self.restarts.push(record);
+            },
+            TaskDelta::UpdateAvailable(value) => {
+                self.update_available = value;
+            },
+            TaskDelta::PullProgress(progress) => {
+                self.pull = Some(progress);
+            },
+            TaskDelta::SpecChanged(fields) => {
+                self.changed_fields = fields;
+            },
         }
     }
 }
@@ -112,6 +157,12 @@ impl TaskState {
 pub struct TaskProgress {
     pub pct: u8,
     pub stage: String,
+    /// The size of the download in bytes.
+    #[serde(default)]
+    pub total: Option<u64>,
+    /// The estimated number of seconds left.
+    #[serde(default)]
+    pub eta: Option<u64>,
 }
 
 impl TaskProgress {
@@ -119,6 +170,8 @@ impl TaskProgress {
         Self {
             pct: 0,
             stage: stage.to_string(),
+            total: None,
+            eta: None,
         }
     }
 }
@@ -134,6 +187,8 @@ pub enum TaskStatus {
     Progress(TaskProgress),
     /// Task is running
     Active,
+    /// Task is running, but doesn't work properly.
+    Unhealthy(String),
     /// Task failed for other reasons.
     Failed(String),
 }
@@ -162,9 +217,23 @@ impl TaskStatus {
         matches!(self, Self::Failed(_))
     }
 
+    pub fn is_unhealthy(&self) -> bool {
+        matches!(self, Self::Unhealthy(_))
+    }
+
     pub fn progress(&self) -> Option<String> {
         match self {
-            Self::Progress(progress) => Some(format!("{} -{}%", progress.stage, progress.pct)),
+            Self::Progress(progress) => {
+                let mut value = format!("{} -{}%", progress.stage, progress.pct);
+                if let Some(total) = progress.total {
+                    let total = Byte::from_u64(total).get_appropriate_unit(UnitType::Decimal);
+                    value.push_str(&format!(" of {:.1}", total));
+                }
+                if let Some(eta) = progress.eta {
+                    value.push_str(&format!(", {}s left", eta));
+                }
+                Some(value)
+            },
             _ => None,
         }
     }
@@ -177,6 +246,7 @@ impl fmt::Display for TaskStatus {
             Self::Pending => write!(f, "Pending"),
             Self::Progress(value) => write!(f, "Progress({} - {}%)", value.stage, value.pct),
             Self::Active => write!(f, "Active"),
+            Self::Unhealthy(reason) => write!(f, "Unhealthy. Reason: {}", reason),
             Self::MissingConfiguration(reason) => write!(f, "Missing configuration. Details: {}", reason),
             Self::Failed(reason) => write!(f, "Failed. Reason: {}", reason),
         }
@@ -189,6 +259,20 @@ pub enum TaskDelta {
     LogRecord(LogRecord),
     LogError(ErrorRecord),
     StatsRecord(StatsData),
+    Restart(RestartRecord),
+    UpdateAvailable(bool),
+    PullProgress(TaskProgress),
+    /// The container is restarted, because the config changed these fields of its spec.
+    SpecChanged(Vec<String>),
+}
+
+/// The connection to the container runtime. It's common for all the tasks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DaemonState {
+    #[default]
+    Available,
+    /// The connection is lost and is restored in the background. The tasks are checked again after that.
+    DockerUnavailable,
 }
 
 impl Frame<StatsData> {
@@ -21,24 +21,26 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
     config::LaunchpadConfig,
-    container::{TaskDelta, TaskId, TaskState},
+    container::{DaemonState, TaskDelta, TaskId, TaskProgress, TaskState},
     errors::ErrorRecord,
     frame::Frame,
     node::{NodeDelta, NodeState},
     session::LaunchpadSession,
     settings::{LaunchpadSettings, PersistentSettings},
+    wallet::{TransferResult, WalletAction, WalletDelta, WalletState},
 };
 
 /// An action sent from UI to the backend.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Action {
     Action(LaunchpadAction),
+    Wallet(WalletAction),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +48,18 @@ pub enum LaunchpadAction {
     Connect,
     ChangeSession(LaunchpadSession),
     SaveSettings(PersistentSettings),
+    /// Stops all the containers and removes the resources that are not permanent.
+    Shutdown,
+    /// Pulls the image of the task again and restarts its container.
+    UpdateImage(TaskId),
+    /// Pulls the images of the tasks, or of all the tasks if `None`, without starting the containers.
+    PullImages {
+        tasks: Option<Vec<TaskId>>,
+    },
+    /// Saves the images of all the tasks to the directory to move them to a host without registry access.
+    ExportImages(PathBuf),
+    /// Loads the images saved by `ExportImages` from the directory.
+    ImportImages(PathBuf),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,7 +69,13 @@ pub enum LaunchpadDelta {
     TaskAdded { id: TaskId, state: TaskState },
     TaskDelta { id: TaskId, delta: TaskDelta },
     NodeDelta(NodeDelta),
+    WalletDelta(WalletDelta),
     AddError(ErrorRecord),
+    /// The images were exported to or imported from the directory. The failures are reported as errors.
+    ImagesTransferred(PathBuf),
+    UpdateDaemon(DaemonState),
+    /// The runners of the tasks are terminated, but the containers are left running.
+    Detached,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,7 +83,13 @@ pub struct LaunchpadState {
     pub config: LaunchpadConfig,
     pub containers: HashMap<TaskId, TaskState>,
     pub node: NodeState,
+    pub wallet: WalletState,
     pub errors: Frame<ErrorRecord>,
+    #[serde(default)]
+    pub daemon: DaemonState,
+    /// The backend doesn't manage the containers anymore, so the statuses of the tasks are frozen.
+    #[serde(default)]
+    pub detached: bool,
 }
 
 impl Default for LaunchpadState {
@@ -72,7 +98,10 @@ impl Default for LaunchpadState {
             config: LaunchpadConfig::default(),
             containers: HashMap::new(),
             node: NodeState::default(),
+            wallet: WalletState::default(),
             errors: Frame::new(30),
+            daemon: DaemonState::default(),
+            detached: false,
         }
     }
 }
@@ -82,9 +111,31 @@ impl Default for LaunchpadState {
 pub enum Reaction {
     State(LaunchpadState),
     Delta(LaunchpadDelta),
+    /// The outcome of a `WalletAction::TransferFunds`. It's not a part of the state.
+    TransferResult(TransferResult),
 }
 
 impl LaunchpadState {
+    /// The combined progress of the images that are pulled ahead of time.
+    pub fn pull_progress(&self) -> Option<TaskProgress> {
+        let pulls: Vec<&TaskProgress> = self
+            .containers
+            .values()
+            .filter_map(|state| state.pull.as_ref())
+            .collect();
+        let count = u32::try_from(pulls.len()).ok().filter(|count| *count > 0)?;
+        let pct = pulls.iter().map(|progress| u32::from(progress.pct)).sum::<u32>() / count;
+        let total = pulls.iter().filter_map(|progress| progress.total).sum::<u64>();
+        let eta = pulls.iter().filter_map(|progress| progress.eta).max();
+        let progress = TaskProgress {
+            pct: u8::try_from(pct).unwrap_or(100),
+            stage: "Pulling the images...".into(),
+            total: Some(total).filter(|total| *total > 0),
+            eta,
+        };
+        Some(progress)
+    }
+
     pub fn apply(&mut self, delta: LaunchpadDelta) {
         use LaunchpadDelta::*;
         match delta {
@@ -106,9 +157,19 @@ impl LaunchpadState {
             AddError(error) => {
                 self.errors.push(error);
             },
+            ImagesTransferred(_) => {},
+            UpdateDaemon(state) => {
+                self.daemon = state;
+            },
+            Detached => {
+                self.detached = true;
+            },
             NodeDelta(delta) => {
                 self.node.apply(delta);
             },
+            WalletDelta(delta) => {
+                self.wallet.apply(delta);
+            },
         }
     }
 }
@@ -21,9 +21,14 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::path::PathBuf;
-use std::str::FromStr;
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    path::PathBuf,
+    str::FromStr,
+};
 
+use byte_unit::Byte;
 use serde::{Deserialize, Serialize};
 use tari_common_types::tari_address::TariAddress;
 use thiserror::Error;
@@ -72,6 +77,60 @@ impl MmProxyConfig {
     }
 }
 
+/// Replaces the registry or the tag of a single image.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ImageOverride {
+    pub registry: Option<String>,
+    pub tag: Option<String>,
+}
+
+/// The limits of the resources of a container. The values that are not set are unlimited.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ContainerLimits {
+    /// The number of CPUs the container can use, e.g. `1.5`.
+    pub cpus: Option<f64>,
+    /// The weight of the container for the CPU time. The default weight is `1024`.
+    pub cpu_shares: Option<u64>,
+    /// The CPUs the container can run on, e.g. `0-3` or `0,1`.
+    pub cpuset: Option<String>,
+    /// The memory limit, e.g. `2 GB`.
+    pub memory: Option<Byte>,
+    /// The limit of the memory and the swap together.
+    pub memory_swap: Option<Byte>,
+    /// The maximal number of the processes.
+    pub pids: Option<u64>,
+}
+
+/// The API endpoint of a container runtime. Podman is supported through its Docker compatible API.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContainerEndpoint {
+    /// The default local socket (or named pipe) of Docker.
+    #[default]
+    Local,
+    /// Follows the `DOCKER_HOST`, `DOCKER_TLS_VERIFY` and `DOCKER_CERT_PATH` environment variables.
+    Env,
+    /// A unix socket or a named pipe. For rootless Podman it's usually `/run/user/<uid>/podman/podman.sock`.
+    Socket { path: PathBuf },
+    /// A plain TCP connection, e.g. `tcp://127.0.0.1:2375`.
+    Tcp { address: String },
+    /// A TCP connection secured with TLS. The `cert_path` folder must contain `key.pem`, `cert.pem` and `ca.pem`.
+    Tls { address: String, cert_path: PathBuf },
+}
+
+impl ContainerEndpoint {
+    /// The runtime runs on this host, so the published ports are reachable on the loopback interface.
+    pub fn is_local(&self) -> bool {
+        match self {
+            Self::Local | Self::Socket { .. } => true,
+            Self::Env => env::var("DOCKER_HOST")
+                .map(|host| host.starts_with("unix://") || host.starts_with("npipe://"))
+                .unwrap_or(true),
+            Self::Tcp { .. } | Self::Tls { .. } => false,
+        }
+    }
+}
+
 /// Tari Launchpad configuration struct. This will generally be populated from some front-end or persistent storage
 /// file and is used to generate the environment variables needed to configure and run the various docker containers.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -90,6 +149,32 @@ pub struct PersistentSettings {
     pub registry: Option<String>,
     /// The docker tag to use. By default, we use 'latest'
     pub tag: Option<String>,
+    /// The registries and the tags of the particular images by the image name, e.g. `minotari_node`. They take
+    /// precedence over the `registry` and the `tag`.
+    #[serde(default)]
+    pub image_overrides: HashMap<String, ImageOverride>,
+    /// The resource limits of the containers by the image name, e.g. `xmrig`.
+    #[serde(default)]
+    pub container_limits: HashMap<String, ContainerLimits>,
+    /// The ports of the host the ports of the containers are published on by the image name and the port of the
+    /// container, e.g. `minotari_node` with `"18142" = 28142`. The ports that are not set are published as is. The
+    /// ports of the containers are strings, because TOML supports string keys only.
+    #[serde(default)]
+    pub port_overrides: HashMap<String, HashMap<String, u16>>,
+    /// The images whose ports are reachable from other hosts, e.g. `grafana`. The ports of other images are bound to
+    /// the loopback interface.
+    #[serde(default)]
+    pub remote_access: HashSet<String>,
+    /// How many images are pulled at the same time when they are pulled ahead of time.
+    #[serde(default)]
+    pub parallel_pulls: Option<usize>,
+    /// Keeps the containers running when the launchpad exits, e.g. to continue mining without the UI. The running
+    /// containers are taken over on the next start.
+    #[serde(default)]
+    pub leave_running: bool,
+    /// The API endpoint of Docker or Podman. By default, the local Docker socket is used.
+    #[serde(default)]
+    pub container_endpoint: ContainerEndpoint,
 }
 
 impl PersistentSettings {
@@ -109,6 +194,18 @@ impl PersistentSettings {
         self.xmrig = Some(XmRigConfig::default());
     }
 
+    /// Sets the registry of the Tari images. An empty value resets it to the default.
+    pub fn set_registry<S: Into<String>>(&mut self, registry: S) {
+        let registry = registry.into();
+        self.registry = Some(registry).filter(|value| !value.is_empty());
+    }
+
+    /// Sets the tag of the Tari images. An empty value resets it to the default.
+    pub fn set_tag<S: Into<String>>(&mut self, tag: S) {
+        let tag = tag.into();
+        self.tag = Some(tag).filter(|value| !value.is_empty());
+    }
+
     pub fn set_monero_mining_address<S: Into<String>>(&mut self, address: S) {
         if self.xmrig.is_none() {
             self.new_xmrig_settings();
@@ -171,6 +268,10 @@ pub struct LaunchpadSettings {
     pub data_directory: PathBuf,
     /// The tor control password to share among containers.
     pub tor_control_password: String,
+    /// The hash of the tor control password. It's salted, so it's computed once to keep the args of Tor the same.
+    pub tor_hashed_password: String,
+    /// The password of the wallet files. It's generated once and kept with the tor passwords.
+    pub wallet_password: String,
     pub with_monitoring: bool,
     pub with_tor: bool,
     pub saved_settings: PersistentSettings,
@@ -181,6 +282,8 @@ impl Default for LaunchpadSettings {
         Self {
             data_directory: PathBuf::default(),
             tor_control_password: String::new(),
+            tor_hashed_password: String::new(),
+            wallet_password: String::new(),
             with_monitoring: true,
             with_tor: true,
             saved_settings: PersistentSettings::default(),
@@ -193,7 +296,7 @@ impl Default for LaunchpadSettings {
 pub struct UnsupportedNetwork(String);
 
 /// Supported networks for the launchpad
-#[derive(Serialize, Debug, Deserialize, Clone, Copy)]
+#[derive(Serialize, Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum TariNetwork {
     Igor,
     Nextnet,
@@ -235,8 +338,24 @@ impl TryFrom<&str> for TariNetwork {
         match value {
             "igor" => Ok(TariNetwork::Igor),
             "nextnet" => Ok(TariNetwork::Nextnet),
+            "stagenet" => Ok(TariNetwork::Stagenet),
             "mainnet" => Ok(TariNetwork::Mainnet),
             other => Err(UnsupportedNetwork(other.to_owned())),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn port_overrides_round_trip() {
+        let mut settings = PersistentSettings::default();
+        let ports = HashMap::from([("18142".to_string(), 28142)]);
+        settings.port_overrides.insert("minotari_node".to_string(), ports);
+        let data = toml::to_string(&settings).unwrap();
+        let restored = PersistentSettings::try_from(data.as_str()).unwrap();
+        assert_eq!(restored.port_overrides, settings.port_overrides);
+    }
+}
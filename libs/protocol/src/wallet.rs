@@ -234,3 +234,12 @@ pub struct TransferFunds {
     pub fee: u64,
     pub message: String,
 }
+
+/// The outcome of a transfer of funds to a single recipient.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferResult {
+    pub address: String,
+    pub tx_id: String,
+    pub is_success: bool,
+    pub failure_message: String,
+}
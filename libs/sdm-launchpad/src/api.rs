@@ -72,6 +72,9 @@ impl SdmWorker {
                 Reaction::Delta(delta) => {
                     state.apply(delta);
                 },
+                Reaction::TransferResult(result) => {
+                    log::info!("The transfer is finished: {:?}", result);
+                },
             }
         }
     }
@@ -21,27 +21,78 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::Error;
 use log::*;
+use serde::{Deserialize, Serialize};
 use tari_launchpad_protocol::{
-    container::{TaskDelta, TaskId, TaskProgress, TaskState, TaskStatus},
+    container::{DaemonState, TaskDelta, TaskId, TaskProgress, TaskState, TaskStatus},
+    errors::ErrorRecord,
     launchpad::{Action, LaunchpadAction, LaunchpadDelta, LaunchpadState, Reaction},
-    settings::PersistentSettings,
+    session::LaunchpadSession,
+    settings::{PersistentSettings, TariNetwork},
+    wallet::{TransferResult, WalletAction, WalletDelta},
+};
+use tari_sdm::{
+    archive,
+    driver::DockerDriver,
+    ids::ManagedTask,
+    image::{fqdn, ManagedContainer},
+    utils::create_password,
+    Report, ReportEnvelope, SdmScope,
 };
-use tari_sdm::{ids::ManagedTask, utils::create_password, Report, ReportEnvelope, SdmScope};
 use tari_sdm_assets::configurator::Configurator;
-use tokio::{select, sync::mpsc};
+use tokio::{
+    select,
+    sync::{mpsc, watch},
+};
+use tor_hash_passwd::EncryptedKey;
 
 use crate::{
     node_grpc::NodeGrpc,
     resources::{
-        config::{LaunchpadProtocol, LaunchpadSettings},
-        images, networks, volumes,
+        config::{LaunchpadConfig, LaunchpadProtocol, LaunchpadSettings},
+        images::{self, grpc_address, BASE_NODE_GRPC_PORT, WALLET_GRPC_PORT},
+        networks, volumes,
     },
+    wallet_grpc::WalletGrpc,
 };
 
+/// How many images are pulled at the same time if it's not configured.
+const DEFAULT_PARALLEL_PULLS: usize = 3;
+/// The smallest CPU limit of a container. The runtime rejects a CPU quota under 1ms of the 100ms period.
+const MIN_CPUS: f64 = 0.01;
+
+/// The passwords shared among the containers. They are a part of the specs of the containers, so they are kept to
+/// adopt the containers on the next start.
+#[derive(Serialize, Deserialize)]
+struct Secrets {
+    tor_control_password: String,
+    /// The hash is salted, so it's stored as well.
+    tor_hashed_password: String,
+    /// The files of the wallet are encrypted with it, so it never changes once the wallet is created.
+    #[serde(default = "legacy_wallet_password")]
+    wallet_password: String,
+}
+
+impl Secrets {
+    fn generate(wallet_password: String) -> Self {
+        let tor_control_password = create_password(16);
+        let tor_hashed_password = EncryptedKey::hash_password(&tor_control_password).to_string();
+        Self {
+            tor_control_password,
+            tor_hashed_password,
+            wallet_password,
+        }
+    }
+}
+
+/// The wallets created by the previous versions use the fixed password.
+fn legacy_wallet_password() -> String {
+    "tari".into()
+}
+
 pub type BusTx = mpsc::UnboundedSender<Action>;
 pub type BusRx = mpsc::UnboundedReceiver<Reaction>;
 
@@ -60,15 +111,70 @@ impl LaunchpadBus {
             outgoing: out_rx,
         })
     }
+
+    /// Saves the images of the configured network to the directory. The tasks are not started, so the containers
+    /// left running and their session are not touched.
+    pub async fn export_images(dir: &Path) -> Result<(), Error> {
+        let settings = LaunchpadWorker::load_configuration().await?;
+        let driver = DockerDriver::connect(&settings.saved_settings.container_endpoint).await?;
+        let images = image_names(settings);
+        let manifest = archive::export_images(&driver, &images, dir).await?;
+        info!("{} images exported to {}", manifest.images.len(), dir.display());
+        Ok(())
+    }
+
+    /// Loads the images saved by `export_images` from the directory.
+    pub async fn import_images(dir: &Path) -> Result<(), Error> {
+        let settings = LaunchpadWorker::load_configuration().await?;
+        let driver = DockerDriver::connect(&settings.saved_settings.container_endpoint).await?;
+        let manifest = archive::import_images(&driver, dir).await?;
+        info!("{} images imported from {}", manifest.images.len(), dir.display());
+        Ok(())
+    }
+}
+
+/// The images of the containers added by `LaunchpadWorker::create_scope` with the registries and the tags resolved
+/// from the settings.
+fn image_names(settings: LaunchpadSettings) -> Vec<String> {
+    let config = LaunchpadConfig {
+        session: LaunchpadSession::default(),
+        settings: Some(settings),
+    };
+    let mut containers: Vec<Box<dyn ManagedContainer<Protocol = LaunchpadProtocol>>> = vec![
+        Box::<images::Tor>::default(),
+        Box::<images::TariBaseNode>::default(),
+        Box::<images::TariWallet>::default(),
+        Box::<images::TariSha3Miner>::default(),
+        Box::<images::Loki>::default(),
+        Box::<images::Promtail>::default(),
+        Box::<images::Grafana>::default(),
+        Box::<images::MmProxy>::default(),
+        Box::<images::XMRig>::default(),
+    ];
+    let mut names: Vec<String> = containers
+        .iter_mut()
+        .map(|container| {
+            container.reconfigure(Some(&config));
+            fqdn(container.as_ref())
+        })
+        .collect();
+    names.sort();
+    names.dedup();
+    names
 }
 
 pub struct LaunchpadWorker {
     state: LaunchpadState,
     scope: SdmScope<LaunchpadProtocol>,
+    /// The connection of the scope to the daemon.
+    daemon: watch::Receiver<DaemonState>,
+    /// The network of the current scope. Every network has its own containers and volumes.
+    network: TariNetwork,
     in_rx: mpsc::UnboundedReceiver<Action>,
     // TODO: Share the sender with the wallet
     out_tx: mpsc::UnboundedSender<Reaction>,
     node_grpc: Option<NodeGrpc>,
+    wallet_grpc: Option<WalletGrpc>,
 }
 
 impl LaunchpadWorker {
@@ -78,14 +184,44 @@ impl LaunchpadWorker {
         in_rx: mpsc::UnboundedReceiver<Action>,
         out_tx: mpsc::UnboundedSender<Reaction>,
     ) -> Result<(), Error> {
-        // TODO: This should respect the configured network and not be hardcoded
-        let mut scope = SdmScope::connect("nextnet")?;
+        let config = Self::load_configuration()
+            .await
+            .map_err(|err| error!("Can't load the configuration: {}", err))
+            .ok();
+        let scope = Self::create_scope(config.as_ref()).await?;
+        let daemon = scope.daemon_state();
+        let network = scope_network(config.as_ref());
+
+        let state = LaunchpadState::default();
+
+        let worker = LaunchpadWorker {
+            state,
+            scope,
+            daemon,
+            network,
+            in_rx,
+            out_tx,
+            node_grpc: None,
+            wallet_grpc: None,
+        };
+        worker.entrypoint(config).await;
+        Ok(())
+    }
+
+    /// Creates a scope of the configured network. The name of the network is used as a prefix of the resources.
+    async fn create_scope(config: Option<&LaunchpadSettings>) -> Result<SdmScope<LaunchpadProtocol>, Error> {
+        let endpoint = config
+            .map(|config| config.saved_settings.container_endpoint.clone())
+            .unwrap_or_default();
+        let network = scope_network(config);
+        let mut scope = SdmScope::connect(network.lower_case(), &endpoint).await?;
         scope.add_network(networks::LocalNet::default())?;
         scope.add_volume(volumes::SharedVolume::default())?;
         scope.add_volume(volumes::SharedGrafanaVolume::default())?;
 
         scope.add_image(images::Tor::default())?;
         scope.add_image(images::TariBaseNode::default())?;
+        scope.add_image(images::TariWallet::default())?;
         scope.add_image(images::TariSha3Miner::default())?;
 
         scope.add_image(images::Loki::default())?;
@@ -94,22 +230,19 @@ impl LaunchpadWorker {
 
         scope.add_image(images::MmProxy::default())?;
         scope.add_image(images::XMRig::default())?;
-
-        let state = LaunchpadState::default();
-
-        let worker = LaunchpadWorker {
-            state,
-            scope,
-            in_rx,
-            out_tx,
-            node_grpc: None,
-        };
-        worker.entrypoint().await;
-        Ok(())
+        Ok(scope)
     }
 
-    async fn entrypoint(mut self) {
-        self.load_configuration().await.ok();
+    async fn entrypoint(mut self, config: Option<LaunchpadSettings>) {
+        if let Some(mut config) = config {
+            self.validate_settings(&mut config.saved_settings).await;
+            let session = Self::take_detached_session(config.data_directory.clone()).await;
+            self.apply_delta(LaunchpadDelta::UpdateConfig(config));
+            if let Some(session) = session {
+                info!("Restoring the session of the containers left running");
+                self.apply_delta(LaunchpadDelta::UpdateSession(session));
+            }
+        }
         // TODO: Watch for the config file changes
         let config = self.state.config.clone();
         self.scope.set_config(Some(config)).ok();
@@ -137,7 +270,78 @@ impl LaunchpadWorker {
             .ok()
     }
 
-    async fn load_configuration(&mut self) -> Result<(), Error> {
+    /// Loads the secrets from `{root}/config/secrets.toml`. They are generated and saved on the first start.
+    async fn load_secrets(mut path: PathBuf) -> Secrets {
+        path.push("config");
+        path.push("secrets.toml");
+        let stored = tokio::fs::read_to_string(&path).await.ok().and_then(|data| {
+            toml::from_str::<Secrets>(data.as_str())
+                .map_err(|e| warn!("{} is not valid TOML. {e}", path.to_string_lossy()))
+                .ok()
+        });
+        if let Some(secrets) = stored {
+            return secrets;
+        }
+        let wallet_password = if Self::has_wallet(path.parent()).await {
+            legacy_wallet_password()
+        } else {
+            create_password(16)
+        };
+        let secrets = Secrets::generate(wallet_password);
+        if let Err(err) = Self::save_secrets(&path, &secrets).await {
+            // The containers will be recreated on the next start
+            warn!("Can't save the secrets: {err}");
+        }
+        secrets
+    }
+
+    /// Checks the wallet was started before, since the data directory has no secrets yet.
+    async fn has_wallet(config_dir: Option<&Path>) -> bool {
+        let Some(wallet_dir) = config_dir.and_then(Path::parent).map(|base| base.join("wallet")) else {
+            return false;
+        };
+        match tokio::fs::read_dir(wallet_dir).await {
+            Ok(mut entries) => matches!(entries.next_entry().await, Ok(Some(_))),
+            Err(_) => false,
+        }
+    }
+
+    async fn save_secrets(path: &Path, secrets: &Secrets) -> Result<(), Error> {
+        let data = toml::to_string(secrets)?;
+        tokio::fs::write(path, data).await?;
+        Ok(())
+    }
+
+    /// Takes the session saved by the previous run that left the containers running, so they are adopted.
+    async fn take_detached_session(mut path: PathBuf) -> Option<LaunchpadSession> {
+        path.push("config");
+        path.push("session.toml");
+        let data = tokio::fs::read_to_string(&path).await.ok()?;
+        // The next runs start with the default session unless the containers are left running again
+        if let Err(err) = tokio::fs::remove_file(&path).await {
+            warn!("Can't remove the session file: {err}");
+        }
+        toml::from_str::<LaunchpadSession>(data.as_str())
+            .map_err(|e| warn!("{} is not valid TOML. {e}", path.to_string_lossy()))
+            .ok()
+    }
+
+    async fn save_detached_session(&self) -> Result<(), Error> {
+        let mut path = self
+            .state
+            .config
+            .settings
+            .as_ref()
+            .map(|s| s.data_directory.clone())
+            .ok_or_else(|| Error::msg("Can't save the session: no settings are attached to the config"))?;
+        path.push("config");
+        path.push("session.toml");
+        let data = toml::to_string(&self.state.config.session)?;
+        tokio::fs::write(path, data).await?;
+        Ok(())
+    }
+
+    async fn load_configuration() -> Result<LaunchpadSettings, Error> {
         let mut configurator = Configurator::init()?;
         let data_directory = configurator.base_path().clone();
         configurator.init_configuration(false).await?;
@@ -145,15 +349,17 @@ impl LaunchpadWorker {
             warn!("Can't parse the settings file. Reverting to defaults.");
             PersistentSettings::default()
         });
+        let secrets = Self::load_secrets(data_directory.clone()).await;
         let config = LaunchpadSettings {
             data_directory,
             with_monitoring: true,
-            tor_control_password: create_password(16),
+            tor_control_password: secrets.tor_control_password,
+            tor_hashed_password: secrets.tor_hashed_password,
+            wallet_password: secrets.wallet_password,
             saved_settings,
             ..Default::default()
         };
-        self.apply_delta(LaunchpadDelta::UpdateConfig(config));
-        Ok(())
+        Ok(config)
     }
 
     async fn step(&mut self) -> Result<(), Error> {
@@ -169,6 +375,11 @@ impl LaunchpadWorker {
                     self.process_report(report).await?;
                 }
             }
+            res = self.daemon.changed() => {
+                if res.is_ok() {
+                    self.process_daemon_state();
+                }
+            }
         }
         Ok(())
     }
@@ -176,7 +387,26 @@ impl LaunchpadWorker {
     async fn process_incoming(&mut self, incoming: Action) -> Result<(), Error> {
         match incoming {
             Action::Action(action) => self.process_action(action).await,
+            Action::Wallet(action) => self.process_wallet_action(action),
+        }
+    }
+
+    fn process_wallet_action(&mut self, action: WalletAction) -> Result<(), Error> {
+        if let Some(wallet_grpc) = self.wallet_grpc.as_ref() {
+            return wallet_grpc.send(action);
+        }
+        match action {
+            WalletAction::TransferFunds(funds) => {
+                let result = TransferResult {
+                    address: funds.address,
+                    tx_id: String::new(),
+                    is_success: false,
+                    failure_message: "The wallet is not running".into(),
+                };
+                self.send(Reaction::TransferResult(result));
+            },
         }
+        Ok(())
     }
 
     async fn process_action(&mut self, action: LaunchpadAction) -> Result<(), Error> {
@@ -190,13 +420,184 @@ impl LaunchpadWorker {
                 let config = self.state.config.clone();
                 self.scope.set_config(Some(config))?;
             },
-            LaunchpadAction::SaveSettings(settings) => {
+            LaunchpadAction::SaveSettings(mut settings) => {
+                self.validate_settings(&mut settings).await;
+                let network = settings.tari_network;
                 self.save_settings(settings).await?;
+                if network == self.network {
+                    let config = self.state.config.clone();
+                    self.scope.set_config(Some(config))?;
+                } else {
+                    self.switch_network(network).await?;
+                }
+            },
+            LaunchpadAction::Shutdown => {
+                let leave_running = self
+                    .state
+                    .config
+                    .settings
+                    .as_ref()
+                    .is_some_and(|settings| settings.saved_settings.leave_running);
+                if leave_running {
+                    info!("Leaving the containers running");
+                    self.save_detached_session().await?;
+                    self.scope.detach().await?;
+                    // The tasks don't report the statuses anymore, so the clients are notified explicitly
+                    self.apply_delta(LaunchpadDelta::Detached);
+                } else {
+                    info!("Shutting down the containers");
+                    self.scope.shutdown().await?;
+                }
+            },
+            LaunchpadAction::UpdateImage(task_id) => {
+                info!("Updating the image of {}", task_id);
+                self.scope.update_image(task_id)?;
+            },
+            LaunchpadAction::PullImages { tasks } => {
+                let parallelism = self
+                    .state
+                    .config
+                    .settings
+                    .as_ref()
+                    .and_then(|settings| settings.saved_settings.parallel_pulls)
+                    .unwrap_or(DEFAULT_PARALLEL_PULLS);
+                info!("Pulling the images with {} parallel pulls", parallelism);
+                self.scope.pull_images(tasks, parallelism)?;
+            },
+            LaunchpadAction::ExportImages(dir) => {
+                info!("Exporting the images to {}", dir.display());
+                let res = self.scope.export_images(&dir).await;
+                self.report_transfer(dir, res);
+            },
+            LaunchpadAction::ImportImages(dir) => {
+                info!("Importing the images from {}", dir.display());
+                let res = self.scope.import_images(&dir).await;
+                self.report_transfer(dir, res);
             },
         }
         Ok(())
     }
 
+    fn report_transfer(&mut self, dir: PathBuf, res: Result<(), Error>) {
+        match res {
+            Ok(()) => {
+                self.apply_delta(LaunchpadDelta::ImagesTransferred(dir));
+            },
+            Err(err) => {
+                let message = format!("Can't transfer the images of {}: {}", dir.display(), err);
+                error!("{}", message);
+                self.add_error(message);
+            },
+        }
+    }
+
+    /// Keeps the number of the mining threads and the CPU limits of the containers within the CPUs of the host.
+    async fn validate_settings(&mut self, settings: &mut PersistentSettings) {
+        let host_cpus = self.scope.host_cpus().await;
+        for (image, limits) in settings.container_limits.iter_mut() {
+            let Some(cpus) = limits.cpus else {
+                continue;
+            };
+            if !cpus.is_finite() || cpus <= 0.0 {
+                let message = format!(
+                    "The CPU limit of {} must be a positive number, but it's {}",
+                    image, cpus
+                );
+                warn!("{}", message);
+                self.add_error(message);
+                limits.cpus = None;
+            } else if cpus < MIN_CPUS {
+                let message = format!(
+                    "The CPU limit of {} can't be {}, it's raised to the minimum of {}",
+                    image, cpus, MIN_CPUS
+                );
+                warn!("{}", message);
+                self.add_error(message);
+                limits.cpus = Some(MIN_CPUS);
+            } else if let Some(host_cpus) = host_cpus.filter(|host_cpus| cpus > *host_cpus as f64) {
+                let message = format!(
+                    "The CPU limit of {} can't be {}, the host has {} CPUs only",
+                    image, cpus, host_cpus
+                );
+                warn!("{}", message);
+                self.add_error(message);
+                limits.cpus = Some(host_cpus as f64);
+            }
+        }
+        let Some(sha3_miner) = settings.sha3_miner.as_mut() else {
+            return;
+        };
+        let Some(cpus) = host_cpus else {
+            return;
+        };
+        if sha3_miner.num_mining_threads > cpus {
+            let message = format!(
+                "The SHA3 miner can't use {} threads, the host has {} CPUs only",
+                sha3_miner.num_mining_threads, cpus
+            );
+            warn!("{}", message);
+            self.add_error(message);
+            sha3_miner.num_mining_threads = cpus;
+        }
+    }
+
+    fn add_error(&mut self, message: String) {
+        let record = ErrorRecord {
+            datetime: chrono::Utc::now().naive_local(),
+            message,
+        };
+        self.apply_delta(LaunchpadDelta::AddError(record));
+    }
+
+    /// Starts the scope of the new network and tears down the containers of the current one. If the new scope can't be
+    /// created, the current one is kept and the network is reverted in the settings.
+    async fn switch_network(&mut self, network: TariNetwork) -> Result<(), Error> {
+        info!(
+            "Switching the network from {} to {}",
+            self.network.lower_case(),
+            network.lower_case()
+        );
+        let scope = match Self::create_scope(self.state.config.settings.as_ref()).await {
+            Ok(scope) => scope,
+            Err(err) => {
+                let message = format!("Can't switch to the {} network: {}", network.lower_case(), err);
+                error!("{}", message);
+                self.add_error(message);
+                return self.revert_network().await;
+            },
+        };
+        if let Err(err) = self.scope.shutdown().await {
+            // The new scope is ready, so the switch goes on
+            let message = format!("Can't shut down the {} network: {}", self.network.lower_case(), err);
+            error!("{}", message);
+            self.add_error(message);
+        }
+        // The reports of the old scope are lost, so the clients are detached here
+        self.node_grpc.take();
+        if self.wallet_grpc.take().is_some() {
+            self.apply_delta(LaunchpadDelta::WalletDelta(WalletDelta::SetActive(false)));
+        }
+        self.scope = scope;
+        self.daemon = self.scope.daemon_state();
+        self.process_daemon_state();
+        self.network = network;
+        let config = self.state.config.clone();
+        self.scope.set_config(Some(config))?;
+        Ok(())
+    }
+
+    /// Saves the settings with the network of the current scope and applies the rest of them to it.
+    async fn revert_network(&mut self) -> Result<(), Error> {
+        let Some(settings) = self.state.config.settings.as_ref() else {
+            return Ok(());
+        };
+        let mut saved_settings = settings.saved_settings.clone();
+        saved_settings.tari_network = self.network;
+        self.save_settings(saved_settings).await?;
+        let config = self.state.config.clone();
+        self.scope.set_config(Some(config))
+    }
+
     fn apply_progress_update(&mut self, task_id: &TaskId, progress: &TaskProgress) {
         self.state
             .containers
@@ -235,15 +636,25 @@ impl LaunchpadWorker {
         path.push("config");
         path.push("settings.toml");
         debug!("Stored settings: {new_settings:?}");
-        let data = toml::to_string(&new_settings).unwrap();
+        let data = toml::to_string(&new_settings)?;
         tokio::fs::write(path, data).await?;
-        if let Some(settings) = self.state.config.settings.as_mut() {
-            // We just checked that this exists above
-            settings.saved_settings = new_settings
+        if let Some(mut settings) = self.state.config.settings.clone() {
+            // The validated values are sent back to the UI
+            settings.saved_settings = new_settings;
+            self.apply_delta(LaunchpadDelta::UpdateConfig(settings));
         }
         Ok(())
     }
 
+    fn process_daemon_state(&mut self) {
+        let state = *self.daemon.borrow_and_update();
+        match state {
+            DaemonState::Available => info!("The container runtime is available"),
+            DaemonState::DockerUnavailable => warn!("The container runtime is unavailable. Reconnecting..."),
+        }
+        self.apply_delta(LaunchpadDelta::UpdateDaemon(state));
+    }
+
     async fn process_report(&mut self, report: ReportEnvelope<LaunchpadProtocol>) -> Result<(), Error> {
         // TODO: Convert to the `LaunchpadDelta` and apply
         match report.details {
@@ -256,7 +667,10 @@ impl LaunchpadWorker {
             },
             Report::Delta(delta) => {
                 if report.task_id == images::TariBaseNode::id() {
-                    self.check_node_grpc(&delta);
+                    self.check_node_grpc(&delta).await;
+                }
+                if report.task_id == images::TariWallet::id() {
+                    self.check_wallet_grpc(&delta).await;
                 }
                 if let TaskDelta::UpdateStatus(TaskStatus::Progress(progress)) = &delta {
                     self.apply_progress_update(&report.task_id, progress);
@@ -272,12 +686,19 @@ impl LaunchpadWorker {
         Ok(())
     }
 
+    /// The address of the gRPC server of the task on the port of the host the container is actually published on.
+    async fn grpc_address(&self, task_id: TaskId, port: u16) -> String {
+        let host_port = self.scope.host_port(&task_id, port).await.unwrap_or(port);
+        grpc_address(host_port)
+    }
+
     // Only called if the task is the base node task
-    fn check_node_grpc(&mut self, delta: &TaskDelta) {
+    async fn check_node_grpc(&mut self, delta: &TaskDelta) {
         if let TaskDelta::UpdateStatus(status) = delta {
             if status.is_active() {
                 if self.node_grpc.is_none() {
-                    let grpc = NodeGrpc::new(self.out_tx.clone());
+                    let address = self.grpc_address(images::TariBaseNode::id(), BASE_NODE_GRPC_PORT).await;
+                    let grpc = NodeGrpc::new(self.out_tx.clone(), address);
                     self.node_grpc = Some(grpc);
                 }
             } else {
@@ -288,4 +709,28 @@ impl LaunchpadWorker {
             }
         }
     }
+
+    // Only called if the task is the wallet task
+    async fn check_wallet_grpc(&mut self, delta: &TaskDelta) {
+        if let TaskDelta::UpdateStatus(status) = delta {
+            if status.is_active() {
+                if self.wallet_grpc.is_none() {
+                    let address = self.grpc_address(images::TariWallet::id(), WALLET_GRPC_PORT).await;
+                    let grpc = WalletGrpc::new(self.out_tx.clone(), address);
+                    self.wallet_grpc = Some(grpc);
+                }
+            } else {
+                // The worker is aborted on drop, so the wallet has to be marked as inactive here
+                if self.wallet_grpc.take().is_some() {
+                    self.apply_delta(LaunchpadDelta::WalletDelta(WalletDelta::SetActive(false)));
+                }
+            }
+        }
+    }
+}
+
+fn scope_network(config: Option<&LaunchpadSettings>) -> TariNetwork {
+    config
+        .map(|config| config.saved_settings.tari_network)
+        .unwrap_or_default()
 }
@@ -29,5 +29,6 @@ mod node_grpc;
 pub mod resources;
 #[cfg(feature = "tauri")]
 pub mod tauri;
+mod wallet_grpc;
 
 pub use bus::LaunchpadBus;
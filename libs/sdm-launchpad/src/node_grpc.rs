@@ -17,13 +17,12 @@ use tokio::{
     time::sleep,
 };
 
-pub const BASE_NODE_GRPC_ADDRESS: &str = "http://127.0.0.1:18142";
 #[derive(Default, Debug)]
 pub struct NodeGrpc {}
 
 impl NodeGrpc {
-    pub fn new(out_tx: mpsc::UnboundedSender<Reaction>) -> Self {
-        let worker = NodeGrpcWorker::new(out_tx);
+    pub fn new(out_tx: mpsc::UnboundedSender<Reaction>, address: String) -> Self {
+        let worker = NodeGrpcWorker::new(out_tx, address);
         tokio::spawn(worker.entrypoint());
         Self {}
     }
@@ -32,18 +31,24 @@ impl NodeGrpc {
 pub struct NodeGrpcWorker {
     // The channel that receives updates about state changes in the node's state
     out_tx: mpsc::UnboundedSender<Reaction>,
+    // The address of the gRPC server published on the host
+    address: String,
     // A long-lived connection to the gRPC server. It is lazily initialized.
     client: Option<Arc<Mutex<BaseNodeClient<tonic::transport::Channel>>>>,
 }
 
 impl NodeGrpcWorker {
-    pub fn new(out_tx: mpsc::UnboundedSender<Reaction>) -> Self {
-        Self { out_tx, client: None }
+    pub fn new(out_tx: mpsc::UnboundedSender<Reaction>, address: String) -> Self {
+        Self {
+            out_tx,
+            address,
+            client: None,
+        }
     }
 
     async fn get_connection(&mut self) -> Result<Arc<Mutex<BaseNodeClient<tonic::transport::Channel>>>, Error> {
         if self.client.is_none() {
-            let client = Arc::new(Mutex::new(BaseNodeClient::connect(BASE_NODE_GRPC_ADDRESS).await?));
+            let client = Arc::new(Mutex::new(BaseNodeClient::connect(self.address.clone()).await?));
             self.client = Some(client);
         }
         Ok(Arc::clone(self.client.as_ref().unwrap()))
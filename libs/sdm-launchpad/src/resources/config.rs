@@ -21,19 +21,30 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::{ops::Deref, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    path::PathBuf,
+};
 
 use anyhow::{anyhow, Error};
 use minotari_node_grpc_client::grpc::NodeIdentity;
 use minotari_wallet_grpc_client::grpc::GetIdentityResponse;
 use serde::Serialize;
 use tari_common_types::{emoji::EmojiId, types::PublicKey};
-use tari_launchpad_protocol::session::LaunchpadSession;
 pub use tari_launchpad_protocol::{
     config::LaunchpadConfig,
     settings::{LaunchpadSettings, TariNetwork},
 };
-use tari_sdm::{config::ManagedProtocol, image::Envs};
+use tari_launchpad_protocol::{
+    session::LaunchpadSession,
+    settings::{ContainerLimits, ImageOverride},
+};
+use tari_sdm::{
+    config::ManagedProtocol,
+    driver::PortExposure,
+    image::{Envs, Limits},
+};
 use tari_utilities::{hex::Hex, ByteArray};
 
 #[derive(Debug)]
@@ -113,23 +124,131 @@ impl TryFrom<GetIdentityResponse> for WalletIdentity {
 pub struct ConnectionSettings {
     pub session: LaunchpadSession,
     pub tor_password: String,
+    pub tor_hashed_password: String,
+    pub wallet_password: String,
     pub tari_network: TariNetwork,
     pub data_directory: PathBuf,
+    pub images: ImageSettings,
+    pub limits: HashMap<String, ContainerLimits>,
+    pub ports: HashMap<String, HashMap<String, u16>>,
+    pub remote_access: HashSet<String>,
+    /// The containers run on this host.
+    pub local_runtime: bool,
 }
 
 impl ConnectionSettings {
     pub fn try_extract(config: &LaunchpadConfig) -> Option<Self> {
         let settings = config.settings.as_ref()?;
+        let saved_settings = &settings.saved_settings;
+        let images = ImageSettings {
+            registry: saved_settings.registry.clone(),
+            tag: saved_settings.tag.clone(),
+            overrides: saved_settings.image_overrides.clone(),
+        };
         Some(ConnectionSettings {
             session: config.session.clone(),
             tor_password: settings.tor_control_password.clone(),
-            tari_network: settings.saved_settings.tari_network,
+            tor_hashed_password: settings.tor_hashed_password.clone(),
+            wallet_password: settings.wallet_password.clone(),
+            tari_network: saved_settings.tari_network,
             data_directory: settings.data_directory.clone(),
+            images,
+            limits: saved_settings.container_limits.clone(),
+            ports: saved_settings.port_overrides.clone(),
+            remote_access: saved_settings.remote_access.clone(),
+            local_runtime: saved_settings.container_endpoint.is_local(),
         })
     }
 }
 
+/// Resolves the registries and the tags of the images. The override of an image comes first, the common values of the
+/// settings are applied to the Tari images only.
+#[derive(Debug, Default)]
+pub struct ImageSettings {
+    registry: Option<String>,
+    tag: Option<String>,
+    overrides: HashMap<String, ImageOverride>,
+}
+
+impl ImageSettings {
+    pub fn tari_registry<'a>(&'a self, image_name: &str, default: &'a str) -> &'a str {
+        self.overridden(image_name, |image| image.registry.as_deref())
+            .or_else(|| non_empty(self.registry.as_deref()))
+            .unwrap_or(default)
+    }
+
+    pub fn tari_tag<'a>(&'a self, image_name: &str, default: &'a str) -> &'a str {
+        self.overridden(image_name, |image| image.tag.as_deref())
+            .or_else(|| non_empty(self.tag.as_deref()))
+            .unwrap_or(default)
+    }
+
+    pub fn registry<'a>(&'a self, image_name: &str, default: &'a str) -> &'a str {
+        self.overridden(image_name, |image| image.registry.as_deref())
+            .unwrap_or(default)
+    }
+
+    pub fn tag<'a>(&'a self, image_name: &str, default: &'a str) -> &'a str {
+        self.overridden(image_name, |image| image.tag.as_deref())
+            .unwrap_or(default)
+    }
+
+    fn overridden<'a, F>(&'a self, image_name: &str, field: F) -> Option<&'a str>
+    where
+        F: FnOnce(&'a ImageOverride) -> Option<&'a str>,
+    {
+        non_empty(self.overrides.get(image_name).and_then(field))
+    }
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.filter(|value| !value.is_empty())
+}
+
 impl ConnectionSettings {
+    /// Applies the resource limits of the image from the settings.
+    pub fn add_limits(&self, image_name: &str, limits: &mut Limits) {
+        let Some(value) = self.limits.get(image_name) else {
+            return;
+        };
+        if let Some(cpus) = value.cpus {
+            limits.cpus(cpus);
+        }
+        if let Some(shares) = value.cpu_shares {
+            limits.cpu_shares(shares);
+        }
+        if let Some(cpuset) = value.cpuset.as_ref() {
+            limits.cpuset(cpuset);
+        }
+        if let Some(memory) = value.memory {
+            limits.memory(memory.as_u64());
+        }
+        if let Some(memory_swap) = value.memory_swap {
+            limits.memory_swap(memory_swap.as_u64());
+        }
+        if let Some(pids) = value.pids {
+            limits.pids(pids);
+        }
+    }
+
+    /// The port of the host the `port` of the image is published on.
+    pub fn host_port(&self, image_name: &str, port: u16) -> u16 {
+        self.ports
+            .get(image_name)
+            .and_then(|ports| ports.get(&port.to_string()))
+            .copied()
+            .unwrap_or(port)
+    }
+
+    /// The interfaces the ports of the image are published on.
+    pub fn exposure(&self, image_name: &str) -> PortExposure {
+        if self.remote_access.contains(image_name) {
+            PortExposure::Lan
+        } else {
+            PortExposure::Loopback
+        }
+    }
+
     pub fn add_tor(&self, module: &str, envs: &mut Envs) {
         let value = format!("password={}", self.tor_password.deref());
         let module = module.to_uppercase();
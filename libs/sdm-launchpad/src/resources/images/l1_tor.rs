@@ -21,22 +21,21 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::ops::Deref;
-
+use anyhow::Error;
 use async_trait::async_trait;
 use log::debug;
 use regex::Regex;
 use tari_launchpad_protocol::container::TaskProgress;
 use tari_sdm::{
+    driver::PortExposure,
     ids::{ManagedTask, TaskId},
     image::{
-        checker::{CheckerContext, CheckerEvent, ContainerChecker},
-        Args, Envs, ManagedContainer, Networks, Ports,
+        checker::{tcp_probe, CheckerContext, CheckerEvent, ContainerChecker, Liveness},
+        Args, Envs, Limits, ManagedContainer, Networks, Ports,
     },
 };
-use tor_hash_passwd::EncryptedKey;
 
-use super::DEFAULT_REGISTRY;
+use super::{host_port, publish_port, DEFAULT_REGISTRY, LATEST_TAG, LIVENESS_THRESHOLD, TOR_SOCKS_PORT};
 use crate::resources::{
     config::{ConnectionSettings, LaunchpadConfig, LaunchpadProtocol},
     networks::LocalNet,
@@ -61,13 +60,21 @@ impl ManagedContainer for Tor {
     type Protocol = LaunchpadProtocol;
 
     fn registry(&self) -> &str {
-        DEFAULT_REGISTRY
+        self.settings.as_ref().map_or(DEFAULT_REGISTRY, |settings| {
+            settings.images.tari_registry(self.image_name(), DEFAULT_REGISTRY)
+        })
     }
 
     fn image_name(&self) -> &str {
         "tor"
     }
 
+    fn tag(&self) -> &str {
+        self.settings.as_ref().map_or(LATEST_TAG, |settings| {
+            settings.images.tari_tag(self.image_name(), LATEST_TAG)
+        })
+    }
+
     fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> Option<bool> {
         debug!("Reconfiguring Tor");
         self.settings = ConnectionSettings::try_extract(config?);
@@ -76,7 +83,12 @@ impl ManagedContainer for Tor {
     }
 
     fn checker(&mut self) -> Box<dyn ContainerChecker<LaunchpadProtocol>> {
-        Box::new(Checker::new())
+        let settings = self.settings.as_ref();
+        // The ports of a remote runtime are not reachable here, so the liveness of Tor is not probed
+        let remote = settings.is_some_and(|settings| !settings.local_runtime);
+        let address =
+            (!remote).then(|| format!("127.0.0.1:{}", host_port(settings, self.image_name(), TOR_SOCKS_PORT)));
+        Box::new(Checker::new(address))
     }
 
     fn args(&self, args: &mut Args) {
@@ -86,8 +98,7 @@ impl ManagedContainer for Tor {
         args.set_pair("--ClientOnly", 1);
         args.set_pair("--ClientUseIPv6", 1);
         if let Some(settings) = self.settings.as_ref() {
-            let hashed = EncryptedKey::hash_password(settings.tor_password.deref());
-            args.set_pair("--HashedControlPassword", hashed);
+            args.set_pair("--HashedControlPassword", &settings.tor_hashed_password);
         }
         args.flag("--allow-missing-torrc");
     }
@@ -99,23 +110,40 @@ impl ManagedContainer for Tor {
     }
 
     fn ports(&self, ports: &mut Ports) {
-        ports.add(9050);
-        ports.add(9051);
+        let settings = self.settings.as_ref();
+        publish_port(settings, self.image_name(), TOR_SOCKS_PORT, ports);
+        // The control port is used by the containers of the network only
+        ports.publish(9051, 9051, PortExposure::None);
     }
 
     fn networks(&self, networks: &mut Networks) {
         networks.add("tor", LocalNet::id());
     }
+
+    fn limits(&self, limits: &mut Limits) {
+        if let Some(settings) = self.settings.as_ref() {
+            settings.add_limits(self.image_name(), limits);
+        }
+    }
 }
 
 struct Checker {
+    /// The SOCKS address published on the host. It's `None` if the runtime is remote.
+    address: Option<String>,
     re: Regex,
+    ready: bool,
+    liveness: Liveness,
 }
 
 impl Checker {
-    fn new() -> Self {
+    fn new(address: Option<String>) -> Self {
         let re = Regex::new(r"Bootstrapped\s+(?P<pct>\d+)%").unwrap();
-        Self { re }
+        Self {
+            address,
+            re,
+            ready: false,
+            liveness: Liveness::new(LIVENESS_THRESHOLD),
+        }
     }
 }
 
@@ -129,13 +157,28 @@ impl ContainerChecker<LaunchpadProtocol> for Checker {
                     let progress = TaskProgress {
                         pct: value as u8,
                         stage: "Bootstrapping...".into(),
+                        total: None,
+                        eta: None,
                     };
                     ctx.report(CheckerEvent::Progress(progress)).ok();
                     if value == 100 {
+                        self.ready = true;
                         ctx.report(CheckerEvent::Ready).ok();
                     }
                 }
             }
         }
     }
+
+    /// The SOCKS port is probed to check Tor is alive after the bootstrapping, unless the runtime is remote.
+    async fn on_interval(&mut self, ctx: &mut CheckerContext<LaunchpadProtocol>) -> Result<(), Error> {
+        if !self.ready {
+            return Ok(());
+        }
+        if let Some(address) = self.address.as_ref() {
+            let probe = tcp_probe(address).await;
+            self.liveness.update(probe, ctx)?;
+        }
+        Ok(())
+    }
 }
@@ -21,6 +21,8 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
+use std::time::Duration;
+
 use anyhow::Error;
 use async_trait::async_trait;
 use log::debug;
@@ -29,14 +31,14 @@ use tari_launchpad_protocol::{container::TaskProgress, settings::BaseNodeConfig}
 use tari_sdm::{
     ids::{ManagedTask, TaskId},
     image::{
-        checker::{CheckerContext, CheckerEvent, ContainerChecker},
-        Args, Envs, ManagedContainer, Mounts, Networks, Ports, Volumes,
+        checker::{grpc_probe, CheckerContext, CheckerEvent, ContainerChecker, Liveness},
+        Args, Envs, Limits, ManagedContainer, Mounts, Networks, Ports, Volumes,
     },
 };
 
 use super::{
-    sync_progress::SyncProgress, Tor, BLOCKCHAIN_PATH, BLOCKCHAIN_VOLUME, DEFAULT_REGISTRY, GENERAL_VOLUME,
-    VAR_TARI_PATH,
+    grpc_address, host_port, publish_port, sync_progress::SyncProgress, tari_network_tag, Tor, BASE_NODE_GRPC_PORT,
+    BLOCKCHAIN_PATH, BLOCKCHAIN_VOLUME, DEFAULT_REGISTRY, GENERAL_VOLUME, LIVENESS_THRESHOLD, VAR_TARI_PATH,
 };
 use crate::resources::{
     config::{ConnectionSettings, LaunchpadConfig, LaunchpadInnerEvent, LaunchpadProtocol},
@@ -64,7 +66,9 @@ impl ManagedContainer for TariBaseNode {
     type Protocol = LaunchpadProtocol;
 
     fn registry(&self) -> &str {
-        DEFAULT_REGISTRY
+        self.settings.as_ref().map_or(DEFAULT_REGISTRY, |settings| {
+            settings.images.tari_registry(self.image_name(), DEFAULT_REGISTRY)
+        })
     }
 
     fn image_name(&self) -> &str {
@@ -72,7 +76,12 @@ impl ManagedContainer for TariBaseNode {
     }
 
     fn tag(&self) -> &str {
-        "latest-nextnet"
+        tari_network_tag(self.settings.as_ref(), self.image_name())
+    }
+
+    fn stop_timeout(&self) -> Duration {
+        // The blockchain database has to be flushed
+        Duration::from_secs(60)
     }
 
     fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> Option<bool> {
@@ -89,7 +98,8 @@ impl ManagedContainer for TariBaseNode {
     }
 
     fn checker(&mut self) -> Box<dyn ContainerChecker<LaunchpadProtocol>> {
-        Box::new(Checker::new())
+        let port = host_port(self.settings.as_ref(), self.image_name(), BASE_NODE_GRPC_PORT);
+        Box::new(Checker::new(grpc_address(port)))
     }
 
     fn args(&self, args: &mut Args) {
@@ -113,8 +123,9 @@ impl ManagedContainer for TariBaseNode {
     }
 
     fn ports(&self, ports: &mut Ports) {
-        ports.add(18_142);
-        ports.add(18_189);
+        let settings = self.settings.as_ref();
+        publish_port(settings, self.image_name(), BASE_NODE_GRPC_PORT, ports);
+        publish_port(settings, self.image_name(), 18_189, ports);
     }
 
     fn networks(&self, networks: &mut Networks) {
@@ -133,24 +144,41 @@ impl ManagedContainer for TariBaseNode {
             mounts.add_volume(SharedVolume::id(), BLOCKCHAIN_PATH);
         }
     }
+
+    fn limits(&self, limits: &mut Limits) {
+        if let Some(settings) = self.settings.as_ref() {
+            settings.add_limits(self.image_name(), limits);
+        }
+    }
 }
 
 /// A helper struct to track the progress of the initial block download.
 struct Checker {
+    /// The gRPC address published on the host.
+    address: String,
     progress: SyncProgress,
     identity_sent: bool,
     ready: bool,
+    liveness: Liveness,
 }
 
 impl Checker {
-    fn new() -> Self {
+    fn new(address: String) -> Self {
         let progress = SyncProgress::new(0, 100);
         Self {
+            address,
             progress,
             identity_sent: false,
             ready: false,
+            liveness: Liveness::new(LIVENESS_THRESHOLD),
         }
     }
+
+    async fn probe(&self) -> Result<(), Error> {
+        let mut client = grpc_probe(BaseNodeGrpcClient::connect(self.address.clone())).await?;
+        grpc_probe(client.identify(grpc::Empty {})).await?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -158,13 +186,14 @@ impl ContainerChecker<LaunchpadProtocol> for Checker {
     /// The interval hook in the base node checker is used to query the base node via gRPC for the current sync
     /// progress. The progress is then reported to the SDM via the `CheckerEvent::Progress` event.
     /// The task is reported as complete (`READY`) once the `sync_state` value from the `get_sync_progress` RPC call
-    /// is `Done`.
+    /// is `Done`. After that the gRPC server is probed to check the node is alive.
     async fn on_interval(&mut self, ctx: &mut CheckerContext<LaunchpadProtocol>) -> Result<(), Error> {
         if self.ready {
-            return Ok(());
+            let probe = self.probe().await;
+            return self.liveness.update(probe, ctx);
         }
         // TODO: Keep the client
-        let mut client = BaseNodeGrpcClient::connect("http://127.0.0.1:18142").await?;
+        let mut client = BaseNodeGrpcClient::connect(self.address.clone()).await?;
 
         if !self.identity_sent {
             let identity = client.identify(grpc::Empty {}).await?.into_inner().try_into()?;
@@ -182,6 +211,8 @@ impl ContainerChecker<LaunchpadProtocol> for Checker {
         let progress = TaskProgress {
             pct: info.block_progress as u8,
             stage: "Syncing blockchain...".into(),
+            total: None,
+            eta: None,
         };
         ctx.report(CheckerEvent::Progress(progress)).ok();
         if done {
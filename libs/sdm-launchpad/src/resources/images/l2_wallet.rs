@@ -0,0 +1,184 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use anyhow::Error;
+use async_trait::async_trait;
+use log::debug;
+use minotari_app_grpc::tari_rpc::{wallet_client::WalletClient, GetIdentityRequest};
+use tari_launchpad_protocol::container::TaskProgress;
+use tari_sdm::{
+    ids::{ManagedTask, TaskId},
+    image::{
+        checker::{grpc_probe, CheckerContext, CheckerEvent, ContainerChecker, Liveness},
+        Args, Envs, Limits, ManagedContainer, Mounts, Networks, Ports, Volumes,
+    },
+};
+
+use super::{
+    grpc_address, host_port, publish_port, tari_network_tag, TariBaseNode, Tor, DEFAULT_REGISTRY, GENERAL_VOLUME,
+    LIVENESS_THRESHOLD, VAR_TARI_PATH, WALLET_GRPC_PORT,
+};
+use crate::resources::{
+    config::{BaseNodeIdentity, ConnectionSettings, LaunchpadConfig, LaunchpadInnerEvent, LaunchpadProtocol},
+    networks::LocalNet,
+    volumes::SharedVolume,
+};
+
+#[derive(Debug, Default)]
+pub struct TariWallet {
+    settings: Option<ConnectionSettings>,
+    /// The identity of the base node the wallet connects to. It's received from the base node checker.
+    identity: Option<BaseNodeIdentity>,
+}
+
+impl ManagedTask for TariWallet {
+    fn id() -> TaskId {
+        "Wallet".into()
+    }
+
+    fn deps() -> Vec<TaskId> {
+        vec![LocalNet::id(), SharedVolume::id(), Tor::id(), TariBaseNode::id()]
+    }
+}
+
+impl ManagedContainer for TariWallet {
+    type Protocol = LaunchpadProtocol;
+
+    fn registry(&self) -> &str {
+        self.settings.as_ref().map_or(DEFAULT_REGISTRY, |settings| {
+            settings.images.tari_registry(self.image_name(), DEFAULT_REGISTRY)
+        })
+    }
+
+    fn image_name(&self) -> &str {
+        "minotari_console_wallet"
+    }
+
+    fn tag(&self) -> &str {
+        tari_network_tag(self.settings.as_ref(), self.image_name())
+    }
+
+    fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> Option<bool> {
+        debug!("Reconfiguring wallet");
+        self.settings = ConnectionSettings::try_extract(config?);
+        let session = &self.settings.as_ref()?.session;
+        Some(session.is_wallet_active())
+    }
+
+    fn on_event(&mut self, event: LaunchpadInnerEvent) {
+        if let LaunchpadInnerEvent::IdentityReady(identity) = event {
+            self.identity = Some(identity);
+        }
+    }
+
+    fn checker(&mut self) -> Box<dyn ContainerChecker<LaunchpadProtocol>> {
+        let port = host_port(self.settings.as_ref(), self.image_name(), WALLET_GRPC_PORT);
+        Box::new(Checker::new(grpc_address(port)))
+    }
+
+    fn args(&self, args: &mut Args) {
+        args.set("--log-config", "/var/tari/config/log4rs.yml");
+        args.flag("--non-interactive-mode");
+    }
+
+    fn envs(&self, envs: &mut Envs) {
+        if let Some(settings) = self.settings.as_ref() {
+            settings.add_common(envs);
+            settings.add_tor("WALLET", envs);
+            envs.set("TARI_WALLET__P2P__TRANSPORT__TYPE", "tor");
+            envs.set(
+                "TARI_WALLET__P2P__TRANSPORT__TOR__CONTROL_ADDRESS",
+                "/dns4/tor/tcp/9051",
+            );
+            envs.set(
+                "TARI_WALLET__P2P__TRANSPORT__TOR__SOCKS_ADDRESS_OVERRIDE",
+                "/dns4/tor/tcp/9050",
+            );
+            envs.set(
+                "TARI_WALLET__P2P__TRANSPORT__TCP__LISTENER_ADDRESS",
+                "/dns4/wallet/tcp/18188",
+            );
+            envs.set("TARI_WALLET_PASSWORD", &settings.wallet_password);
+        }
+        if let Some(identity) = self.identity.as_ref() {
+            envs.set("TARI_WALLET__CUSTOM_BASE_NODE", identity.connection_string());
+        }
+        envs.set("SHELL", "/bin/bash");
+        envs.set("TERM", "linux");
+        envs.set("APP_NAME", "wallet");
+        envs.set("APP_EXEC", "minotari_console_wallet");
+    }
+
+    fn ports(&self, ports: &mut Ports) {
+        let settings = self.settings.as_ref();
+        publish_port(settings, self.image_name(), WALLET_GRPC_PORT, ports);
+        publish_port(settings, self.image_name(), 18_188, ports);
+    }
+
+    fn networks(&self, networks: &mut Networks) {
+        networks.add("wallet", LocalNet::id());
+    }
+
+    fn volumes(&self, volumes: &mut Volumes) {
+        volumes.add(GENERAL_VOLUME);
+    }
+
+    fn mounts(&self, mounts: &mut Mounts) {
+        if let Some(settings) = self.settings.as_ref() {
+            // TODO: Avoid using display here
+            mounts.bind_path(settings.data_directory.display(), VAR_TARI_PATH);
+        }
+    }
+
+    fn limits(&self, limits: &mut Limits) {
+        if let Some(settings) = self.settings.as_ref() {
+            settings.add_limits(self.image_name(), limits);
+        }
+    }
+}
+
+/// Waits for the wallet's gRPC server and shares the identity of the wallet with other tasks.
+struct Checker {
+    /// The gRPC address published on the host.
+    address: String,
+    ready: bool,
+    liveness: Liveness,
+}
+
+impl Checker {
+    fn new(address: String) -> Self {
+        Self {
+            address,
+            ready: false,
+            liveness: Liveness::new(LIVENESS_THRESHOLD),
+        }
+    }
+
+    async fn probe(&self) -> Result<(), Error> {
+        let mut client = grpc_probe(WalletClient::connect(self.address.clone())).await?;
+        grpc_probe(client.identify(GetIdentityRequest {})).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ContainerChecker<LaunchpadProtocol> for Checker {
+    /// The wallet is reported as `READY` once its gRPC server responds with the identity. The identity is
+    /// broadcasted to other tasks as `LaunchpadInnerEvent::WalletIdentityReady`. After that the gRPC server is probed
+    /// to check the wallet is alive.
+    async fn on_interval(&mut self, ctx: &mut CheckerContext<LaunchpadProtocol>) -> Result<(), Error> {
+        if self.ready {
+            let probe = self.probe().await;
+            return self.liveness.update(probe, ctx);
+        }
+        let progress = TaskProgress::new("Waiting for the gRPC server...");
+        ctx.report(CheckerEvent::Progress(progress)).ok();
+        let mut client = WalletClient::connect(self.address.clone()).await?;
+        let identity = client.identify(GetIdentityRequest {}).await?.into_inner().try_into()?;
+        let event = LaunchpadInnerEvent::WalletIdentityReady(identity);
+        ctx.notify(event)?;
+        self.ready = true;
+        ctx.report(CheckerEvent::Ready).ok();
+        Ok(())
+    }
+}
@@ -25,10 +25,10 @@ use log::{info, warn};
 use tari_common_types::tari_address::TariAddress;
 use tari_sdm::{
     ids::{ManagedTask, TaskId},
-    image::{Args, Envs, ManagedContainer, Mounts, Networks, Volumes},
+    image::{Args, Envs, Limits, ManagedContainer, Mounts, Networks, Volumes},
 };
 
-use super::{TariBaseNode, DEFAULT_REGISTRY, GENERAL_VOLUME};
+use super::{tari_network_tag, TariBaseNode, DEFAULT_REGISTRY, GENERAL_VOLUME};
 use crate::resources::{
     config::{ConnectionSettings, LaunchpadConfig, LaunchpadProtocol},
     images::VAR_TARI_PATH,
@@ -40,6 +40,8 @@ use crate::resources::{
 pub struct TariSha3Miner {
     settings: Option<ConnectionSettings>,
     wallet_payment_address: Option<TariAddress>,
+    /// The configured number of the threads. The miner uses all the CPUs if it's not set.
+    num_mining_threads: Option<usize>,
 }
 
 impl ManagedTask for TariSha3Miner {
@@ -56,7 +58,9 @@ impl ManagedContainer for TariSha3Miner {
     type Protocol = LaunchpadProtocol;
 
     fn registry(&self) -> &str {
-        DEFAULT_REGISTRY
+        self.settings.as_ref().map_or(DEFAULT_REGISTRY, |settings| {
+            settings.images.tari_registry(self.image_name(), DEFAULT_REGISTRY)
+        })
     }
 
     fn image_name(&self) -> &str {
@@ -64,13 +68,20 @@ impl ManagedContainer for TariSha3Miner {
     }
 
     fn tag(&self) -> &str {
-        "latest-nextnet"
+        tari_network_tag(self.settings.as_ref(), self.image_name())
     }
 
     fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> Option<bool> {
         self.settings = ConnectionSettings::try_extract(config?);
         let session = &self.settings.as_ref()?.session;
 
+        self.num_mining_threads = config?
+            .settings
+            .as_ref()
+            .and_then(|settings| settings.saved_settings.sha3_miner.as_ref())
+            .map(|sha3_miner| sha3_miner.num_mining_threads)
+            .filter(|threads| *threads > 0);
+
         self.wallet_payment_address = match config?.settings {
             Some(ref settings) if settings.saved_settings.sha3_miner.is_none() => {
                 info!("No Sha3 Miner settings found for the container configuration. Falling back on defaults.");
@@ -93,7 +104,9 @@ impl ManagedContainer for TariSha3Miner {
     fn envs(&self, envs: &mut Envs) {
         if let Some(settings) = self.settings.as_ref() {
             settings.add_common(envs);
-            envs.set("TARI_MINER__NUM_MINING_THREADS", 8); // TODO: Get config num
+            if let Some(threads) = self.num_mining_threads {
+                envs.set("TARI_MINER__NUM_MINING_THREADS", threads);
+            }
             envs.set("TARI_MINER__MINE_ON_TIP_ONLY", 1);
             envs.set(
                 &format!(
@@ -126,4 +139,10 @@ impl ManagedContainer for TariSha3Miner {
             mounts.bind_path(settings.data_directory.display(), VAR_TARI_PATH);
         }
     }
+
+    fn limits(&self, limits: &mut Limits) {
+        if let Some(settings) = self.settings.as_ref() {
+            settings.add_limits(self.image_name(), limits);
+        }
+    }
 }
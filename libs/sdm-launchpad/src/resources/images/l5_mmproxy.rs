@@ -27,14 +27,14 @@ use log::*;
 use tari_launchpad_protocol::settings::MmProxyConfig;
 use tari_sdm::{
     ids::{ManagedTask, TaskId},
-    image::{Args, Envs, ManagedContainer, Mounts, Networks, Volumes},
+    image::{Args, Envs, Limits, ManagedContainer, Mounts, Networks, Volumes},
 };
 
-use super::{TariBaseNode, DEFAULT_REGISTRY, GENERAL_VOLUME, VAR_TARI_PATH};
-use crate::resources::volumes::SharedVolume;
+use super::{tari_network_tag, TariBaseNode, DEFAULT_REGISTRY, GENERAL_VOLUME, VAR_TARI_PATH};
 use crate::resources::{
     config::{ConnectionSettings, LaunchpadConfig, LaunchpadProtocol},
     networks::LocalNet,
+    volumes::SharedVolume,
 };
 
 #[derive(Debug, Default)]
@@ -57,7 +57,9 @@ impl ManagedContainer for MmProxy {
     type Protocol = LaunchpadProtocol;
 
     fn registry(&self) -> &str {
-        DEFAULT_REGISTRY
+        self.settings.as_ref().map_or(DEFAULT_REGISTRY, |settings| {
+            settings.images.tari_registry(self.image_name(), DEFAULT_REGISTRY)
+        })
     }
 
     fn image_name(&self) -> &str {
@@ -65,7 +67,7 @@ impl ManagedContainer for MmProxy {
     }
 
     fn tag(&self) -> &str {
-        "latest-nextnet"
+        tari_network_tag(self.settings.as_ref(), self.image_name())
     }
 
     fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> Option<bool> {
@@ -132,4 +134,10 @@ impl ManagedContainer for MmProxy {
             mounts.bind_path(settings.data_directory.to_string_lossy(), VAR_TARI_PATH);
         }
     }
+
+    fn limits(&self, limits: &mut Limits) {
+        if let Some(settings) = self.settings.as_ref() {
+            settings.add_limits(self.image_name(), limits);
+        }
+    }
 }
@@ -24,10 +24,10 @@
 use tari_launchpad_protocol::settings::XmRigConfig;
 use tari_sdm::{
     ids::{ManagedTask, TaskId},
-    image::{Args, Envs, ManagedContainer, Networks, Volumes},
+    image::{Args, Envs, Limits, ManagedContainer, Networks, Volumes},
 };
 
-use super::{MmProxy, DEFAULT_REGISTRY, GENERAL_VOLUME};
+use super::{MmProxy, DEFAULT_REGISTRY, GENERAL_VOLUME, LATEST_TAG};
 use crate::resources::{
     config::{ConnectionSettings, LaunchpadConfig, LaunchpadProtocol},
     networks::LocalNet,
@@ -53,13 +53,21 @@ impl ManagedContainer for XMRig {
     type Protocol = LaunchpadProtocol;
 
     fn registry(&self) -> &str {
-        DEFAULT_REGISTRY
+        self.settings.as_ref().map_or(DEFAULT_REGISTRY, |settings| {
+            settings.images.tari_registry(self.image_name(), DEFAULT_REGISTRY)
+        })
     }
 
     fn image_name(&self) -> &str {
         "xmrig"
     }
 
+    fn tag(&self) -> &str {
+        self.settings.as_ref().map_or(LATEST_TAG, |settings| {
+            settings.images.tari_tag(self.image_name(), LATEST_TAG)
+        })
+    }
+
     fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> Option<bool> {
         self.settings = ConnectionSettings::try_extract(config?);
         let session = &self.settings.as_ref()?.session;
@@ -95,4 +103,10 @@ impl ManagedContainer for XMRig {
     fn volumes(&self, volumes: &mut Volumes) {
         volumes.add(GENERAL_VOLUME);
     }
+
+    fn limits(&self, limits: &mut Limits) {
+        if let Some(settings) = self.settings.as_ref() {
+            settings.add_limits(self.image_name(), limits);
+        }
+    }
 }
@@ -23,10 +23,10 @@
 
 use tari_sdm::{
     ids::{ManagedTask, TaskId},
-    image::{Envs, ManagedContainer, Mounts, Networks, Ports, Volumes},
+    image::{Envs, Limits, ManagedContainer, Mounts, Networks, Ports, Volumes},
 };
 
-use super::GRAFANA_REGISTRY;
+use super::{publish_port, GRAFANA_REGISTRY, LATEST_TAG};
 use crate::resources::{
     config::{ConnectionSettings, LaunchpadConfig, LaunchpadProtocol},
     images::{GENERAL_VOLUME, GRAFANA_DEFAULTS_PATH, GRAFANA_PROVISION_PATH, GRAFANA_VOLUME, VAR_TARI_PATH},
@@ -53,13 +53,21 @@ impl ManagedContainer for Grafana {
     type Protocol = LaunchpadProtocol;
 
     fn registry(&self) -> &str {
-        GRAFANA_REGISTRY
+        self.settings.as_ref().map_or(GRAFANA_REGISTRY, |settings| {
+            settings.images.registry(self.image_name(), GRAFANA_REGISTRY)
+        })
     }
 
     fn image_name(&self) -> &str {
         "grafana"
     }
 
+    fn tag(&self) -> &str {
+        self.settings.as_ref().map_or(LATEST_TAG, |settings| {
+            settings.images.tag(self.image_name(), LATEST_TAG)
+        })
+    }
+
     fn envs(&self, envs: &mut Envs) {
         let path = concat!(
             "/usr/share/grafana/bin:",
@@ -82,7 +90,7 @@ impl ManagedContainer for Grafana {
     }
 
     fn ports(&self, ports: &mut Ports) {
-        ports.add(18_300);
+        publish_port(self.settings.as_ref(), self.image_name(), 18_300, ports);
     }
 
     fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> Option<bool> {
@@ -114,4 +122,10 @@ impl ManagedContainer for Grafana {
             );
         }
     }
+
+    fn limits(&self, limits: &mut Limits) {
+        if let Some(settings) = self.settings.as_ref() {
+            settings.add_limits(self.image_name(), limits);
+        }
+    }
 }
@@ -23,10 +23,10 @@
 
 use tari_sdm::{
     ids::{ManagedTask, TaskId},
-    image::{Args, Envs, ManagedContainer, Mounts, Networks, Ports, Volumes},
+    image::{Args, Envs, Limits, ManagedContainer, Mounts, Networks, Ports, Volumes},
 };
 
-use super::GRAFANA_REGISTRY;
+use super::{publish_port, GRAFANA_REGISTRY, LATEST_TAG};
 use crate::resources::{
     config::{ConnectionSettings, LaunchpadConfig, LaunchpadProtocol},
     images::{Grafana, GENERAL_VOLUME, GRAFANA_VOLUME, PROMTAIL_CONFIG_PATH, VAR_TARI_PATH},
@@ -58,13 +58,21 @@ impl ManagedContainer for Promtail {
     type Protocol = LaunchpadProtocol;
 
     fn registry(&self) -> &str {
-        GRAFANA_REGISTRY
+        self.settings.as_ref().map_or(GRAFANA_REGISTRY, |settings| {
+            settings.images.registry(self.image_name(), GRAFANA_REGISTRY)
+        })
     }
 
     fn image_name(&self) -> &str {
         "promtail"
     }
 
+    fn tag(&self) -> &str {
+        self.settings.as_ref().map_or(LATEST_TAG, |settings| {
+            settings.images.tag(self.image_name(), LATEST_TAG)
+        })
+    }
+
     fn envs(&self, envs: &mut Envs) {
         let path = concat!(
             "/usr/share/grafana/bin:",
@@ -91,7 +99,7 @@ impl ManagedContainer for Promtail {
     }
 
     fn ports(&self, ports: &mut Ports) {
-        ports.add(18_980);
+        publish_port(self.settings.as_ref(), self.image_name(), 18_980, ports);
     }
 
     fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> Option<bool> {
@@ -119,4 +127,10 @@ impl ManagedContainer for Promtail {
             );
         }
     }
+
+    fn limits(&self, limits: &mut Limits) {
+        if let Some(settings) = self.settings.as_ref() {
+            settings.add_limits(self.image_name(), limits);
+        }
+    }
 }
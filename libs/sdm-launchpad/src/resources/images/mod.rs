@@ -23,6 +23,7 @@
 
 mod l1_tor;
 mod l2_base_node;
+mod l2_wallet;
 mod l3_miner;
 mod l5_mmproxy;
 mod l5_xmrig;
@@ -33,16 +34,32 @@ mod sync_progress;
 
 pub use l1_tor::Tor;
 pub use l2_base_node::TariBaseNode;
+pub use l2_wallet::TariWallet;
 pub use l3_miner::TariSha3Miner;
 pub use l5_mmproxy::MmProxy;
 pub use l5_xmrig::XMRig;
 pub use l8_grafana::Grafana;
 pub use l8_loki::Loki;
 pub use l8_promtail::Promtail;
+use tari_sdm::{driver::PortExposure, image::Ports};
+
+use crate::resources::config::{ConnectionSettings, TariNetwork};
+
+/// The number of the failed liveness probes in a row after which a container is unhealthy.
+const LIVENESS_THRESHOLD: u32 = 3;
+
+/// The gRPC port of the base node in the container.
+pub const BASE_NODE_GRPC_PORT: u16 = 18_142;
+/// The gRPC port of the wallet in the container.
+pub const WALLET_GRPC_PORT: u16 = 18_143;
+/// The SOCKS port of Tor in the container.
+const TOR_SOCKS_PORT: u16 = 9050;
 
 static DEFAULT_REGISTRY: &str = "ghcr.io/tari-project";
 static GRAFANA_REGISTRY: &str = "grafana";
 
+static LATEST_TAG: &str = "latest";
+
 static GENERAL_VOLUME: &str = "/var/tari";
 static BLOCKCHAIN_VOLUME: &str = "/blockchain";
 static GRAFANA_VOLUME: &str = "/grafana";
@@ -54,3 +71,38 @@ static GRAFANA_DEFAULTS_PATH: &str = "/usr/share/grafana/conf/defaults.ini";
 static GRAFANA_PROVISION_PATH: &str = "/etc/grafana/provisioning/datasources/all.yml";
 static LOKI_DEFAULTS_PATH: &str = "/usr/share/grafana/conf/defaults.ini";
 static PROMTAIL_CONFIG_PATH: &str = "/etc/promtail/config.yml";
+
+/// The tag of the Tari images that are built for the network.
+fn network_tag(network: TariNetwork) -> &'static str {
+    match network {
+        TariNetwork::Igor => "latest-igor",
+        TariNetwork::Nextnet => "latest-nextnet",
+        TariNetwork::Stagenet => "latest-stagenet",
+        TariNetwork::Mainnet => "latest-mainnet",
+    }
+}
+
+/// Resolves the tag of a Tari image that depends on the network. The configured tags take precedence.
+fn tari_network_tag<'a>(settings: Option<&'a ConnectionSettings>, image_name: &str) -> &'a str {
+    match settings {
+        Some(settings) => settings.images.tari_tag(image_name, network_tag(settings.tari_network)),
+        None => network_tag(TariNetwork::default()),
+    }
+}
+
+/// The port of the host the `port` of the image is published on. The ports without settings are published as is.
+fn host_port(settings: Option<&ConnectionSettings>, image_name: &str, port: u16) -> u16 {
+    settings.map_or(port, |settings| settings.host_port(image_name, port))
+}
+
+/// Publishes the `port` of the image on the port of the host from the settings. The port is reachable from other
+/// hosts only if the remote access to the image is allowed.
+fn publish_port(settings: Option<&ConnectionSettings>, image_name: &str, port: u16, ports: &mut Ports) {
+    let exposure = settings.map_or(PortExposure::Loopback, |settings| settings.exposure(image_name));
+    ports.publish(host_port(settings, image_name, port), port, exposure);
+}
+
+/// The address of a gRPC server published on the `port` of the host.
+pub fn grpc_address(port: u16) -> String {
+    format!("http://127.0.0.1:{}", port)
+}
@@ -0,0 +1,304 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{collections::VecDeque, time::Duration};
+
+use anyhow::Error;
+use minotari_app_grpc::tari_rpc::{
+    payment_recipient::PaymentType, wallet_client::WalletClient, Empty, GetAddressResponse, GetBalanceRequest,
+    GetBalanceResponse, PaymentRecipient, TransactionEvent, TransactionEventRequest, TransferRequest,
+};
+use tari_launchpad_protocol::{
+    errors::ErrorRecord,
+    launchpad::{LaunchpadDelta, LaunchpadDelta::AddError, Reaction},
+    wallet::{MyIdentity, TransferFunds, TransferResult, WalletAction, WalletBalance, WalletDelta, WalletTransaction},
+};
+use tari_sdm::utils::TaskGuard;
+use tokio::{
+    select,
+    sync::mpsc,
+    time::{sleep, Instant},
+};
+
+/// How long a requested action waits for the connection to the wallet before it fails.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub struct WalletGrpc {
+    // The channel to send user-defined actions to the worker with the time they were requested
+    actions_tx: mpsc::UnboundedSender<(Instant, WalletAction)>,
+    // The worker is aborted when the instance is dropped
+    _worker: TaskGuard<()>,
+}
+
+impl WalletGrpc {
+    pub fn new(out_tx: mpsc::UnboundedSender<Reaction>, address: String) -> Self {
+        let (actions_tx, actions_rx) = mpsc::unbounded_channel();
+        let worker = WalletGrpcWorker::new(out_tx, actions_rx, address);
+        let handle = tokio::spawn(worker.entrypoint());
+        Self {
+            actions_tx,
+            _worker: handle.into(),
+        }
+    }
+
+    /// Passes the action to the worker. The action is processed once the wallet's gRPC server is reachable, or fails
+    /// if it's not reachable for too long.
+    pub fn send(&self, action: WalletAction) -> Result<(), Error> {
+        self.actions_tx
+            .send((Instant::now(), action))
+            .map_err(|_| Error::msg("The wallet gRPC worker is not running"))
+    }
+}
+
+pub struct WalletGrpcWorker {
+    // The channel that receives updates about state changes in the wallet's state
+    out_tx: mpsc::UnboundedSender<Reaction>,
+    // The channel that receives actions to perform with the wallet
+    actions_rx: mpsc::UnboundedReceiver<(Instant, WalletAction)>,
+    // The actions that wait for the connection to the wallet
+    pending: VecDeque<(Instant, WalletAction)>,
+    // The address of the gRPC server published on the host
+    address: String,
+    // A long-lived connection to the gRPC server. It is lazily initialized.
+    client: Option<WalletClient<tonic::transport::Channel>>,
+    // Whether the wallet was reported as active
+    active: bool,
+}
+
+impl WalletGrpcWorker {
+    pub fn new(
+        out_tx: mpsc::UnboundedSender<Reaction>,
+        actions_rx: mpsc::UnboundedReceiver<(Instant, WalletAction)>,
+        address: String,
+    ) -> Self {
+        Self {
+            out_tx,
+            actions_rx,
+            pending: VecDeque::new(),
+            address,
+            client: None,
+            active: false,
+        }
+    }
+
+    async fn get_connection(&mut self) -> Result<WalletClient<tonic::transport::Channel>, Error> {
+        if self.client.is_none() {
+            let client = WalletClient::connect(self.address.clone()).await?;
+            self.client = Some(client);
+        }
+        Ok(self.client.clone().unwrap())
+    }
+
+    /// Carries out the routine work for the Wallet gRPC worker. It does a few things:
+    /// * Fetches the address of the wallet
+    /// * Subscribes to the transaction events
+    /// * Polls the wallet for the balance
+    /// * Performs the actions requested by the user
+    ///
+    /// It returns only if the connection to the wallet was lost.
+    async fn routine(&mut self) -> Result<(), Error> {
+        let mut client = self.get_connection().await?;
+        // Update the wallet identity
+        let address = client.get_address(Empty {}).await?;
+        let address = address.into_inner();
+        self.process_address(address);
+
+        let mut events = client
+            .stream_transaction_events(TransactionEventRequest {})
+            .await?
+            .into_inner();
+        self.set_active(true);
+        // The actions requested while the wallet was unreachable
+        while let Some((_, action)) = self.pending.pop_front() {
+            self.process_action(&mut client, action).await;
+        }
+
+        let mut interval = tokio::time::interval(Duration::from_millis(5_000));
+        loop {
+            select! {
+                _ = interval.tick() => {
+                    let balance = client.get_balance(GetBalanceRequest {}).await?;
+                    let balance = balance.into_inner();
+                    self.process_balance(balance);
+                }
+                event = events.message() => {
+                    let event = event?.ok_or_else(|| Error::msg("The stream of transaction events was closed"))?;
+                    if let Some(transaction) = event.transaction {
+                        self.process_transaction(transaction);
+                    }
+                }
+                action = self.actions_rx.recv() => {
+                    if let Some((_, action)) = action {
+                        self.process_action(&mut client, action).await;
+                    }
+                }
+            }
+        }
+    }
+
+    pub async fn entrypoint(mut self) {
+        loop {
+            let result = self.routine().await;
+            if let Err(err) = result {
+                log::error!("Wallet grpc routine failed: {}", err);
+            }
+            // Reconnect on the next attempt
+            self.client.take();
+            self.set_active(false);
+            self.fail_expired();
+            sleep(Duration::from_millis(5_000)).await;
+        }
+    }
+
+    fn set_active(&mut self, active: bool) {
+        if self.active != active {
+            self.active = active;
+            self.send_update(WalletDelta::SetActive(active));
+        }
+    }
+
+    fn send_update(&mut self, delta: WalletDelta) {
+        let msg = Reaction::Delta(LaunchpadDelta::WalletDelta(delta));
+        if let Err(e) = self.out_tx.send(msg) {
+            log::error!("Can't send update for the wallet: {e}");
+        }
+    }
+
+    fn send_result(&mut self, result: TransferResult) {
+        if let Err(e) = self.out_tx.send(Reaction::TransferResult(result)) {
+            log::error!("Can't send the result of the transfer: {e}");
+        }
+    }
+
+    fn send_error<S: Into<String>>(&mut self, error: S) {
+        let record = ErrorRecord {
+            datetime: chrono::Utc::now().naive_local(),
+            message: error.into(),
+        };
+        let msg = Reaction::Delta(AddError(record));
+        if let Err(e) = self.out_tx.send(msg) {
+            log::error!("Can't send error for the wallet: {e}");
+        }
+    }
+
+    fn process_address(&mut self, address: GetAddressResponse) {
+        match MyIdentity::try_from(address.address.as_slice()) {
+            Ok(id) => {
+                let delta = WalletDelta::SetAddress(id);
+                self.send_update(delta);
+            },
+            Err(err) => {
+                log::error!("Failed to parse wallet address: {}", err);
+                self.send_error(err);
+            },
+        }
+    }
+
+    fn process_balance(&mut self, balance: GetBalanceResponse) {
+        let balance = WalletBalance {
+            available: balance.available_balance.into(),
+            pending_incoming: balance.pending_incoming_balance.into(),
+            pending_outgoing: balance.pending_outgoing_balance.into(),
+            timelocked: balance.timelocked_balance.into(),
+        };
+        let delta = WalletDelta::UpdateBalance(balance);
+        self.send_update(delta);
+    }
+
+    fn process_transaction(&mut self, event: TransactionEvent) {
+        if event.event == "not_supported" {
+            return;
+        }
+        let transaction = WalletTransaction {
+            event: event.event,
+            tx_id: event.tx_id,
+            status: event.status,
+            direction: event.direction,
+            amount: event.amount,
+            message: event.message,
+            is_coinbase: event.is_coinbase,
+        };
+        let delta = WalletDelta::LogTransaction(transaction);
+        self.send_update(delta);
+    }
+
+    /// Fails the actions that wait for the connection to the wallet for too long.
+    fn fail_expired(&mut self) {
+        while let Ok(item) = self.actions_rx.try_recv() {
+            self.pending.push_back(item);
+        }
+        while let Some((requested_at, _)) = self.pending.front() {
+            if requested_at.elapsed() < CONNECT_TIMEOUT {
+                break;
+            }
+            if let Some((_, action)) = self.pending.pop_front() {
+                self.fail_action(action, "The wallet is not reachable");
+            }
+        }
+    }
+
+    fn fail_action(&mut self, action: WalletAction, reason: &str) {
+        match action {
+            WalletAction::TransferFunds(funds) => {
+                let result = TransferResult {
+                    address: funds.address,
+                    tx_id: String::new(),
+                    is_success: false,
+                    failure_message: reason.into(),
+                };
+                self.send_result(result);
+            },
+        }
+    }
+
+    async fn process_action(&mut self, client: &mut WalletClient<tonic::transport::Channel>, action: WalletAction) {
+        match action {
+            WalletAction::TransferFunds(funds) => self.transfer_funds(client, funds).await,
+        }
+    }
+
+    /// Sends funds to the recipient and reports the result back with the pending outgoing transaction.
+    async fn transfer_funds(&mut self, client: &mut WalletClient<tonic::transport::Channel>, funds: TransferFunds) {
+        let recipient = PaymentRecipient {
+            address: funds.address.clone(),
+            amount: funds.amount,
+            fee_per_gram: funds.fee,
+            message: funds.message.clone(),
+            payment_type: PaymentType::StandardMimblewimble as i32,
+        };
+        let request = TransferRequest {
+            recipients: vec![recipient],
+        };
+        let results = match client.transfer(request).await {
+            Ok(response) => response.into_inner().results,
+            Err(err) => {
+                log::error!("Failed to transfer funds: {}", err);
+                self.fail_action(WalletAction::TransferFunds(funds), err.message());
+                return;
+            },
+        };
+        for result in results {
+            let result = TransferResult {
+                address: result.address,
+                tx_id: result.transaction_id.to_string(),
+                is_success: result.is_success,
+                failure_message: result.failure_message,
+            };
+            if result.is_success {
+                // The events of the transaction come later, so the pending transfer is shown right away
+                let transaction = WalletTransaction {
+                    event: "sent".into(),
+                    tx_id: result.tx_id.clone(),
+                    status: "Pending".into(),
+                    direction: "Outbound".into(),
+                    amount: funds.amount,
+                    message: funds.message.clone(),
+                    is_coinbase: false,
+                };
+                self.send_update(WalletDelta::LogTransaction(transaction));
+            }
+            self.send_result(result);
+        }
+    }
+}
@@ -93,9 +93,11 @@ impl TestStateInner {
         let state = self.state.as_ref().ok_or(TestError::NoState)?;
         let mut new_session = state.config.session.clone();
         func(&mut new_session);
-        let event = LaunchpadAction::ChangeSession(new_session);
-        let action = Action::Action(event);
-        self.bus.incoming.send(action)?;
+        self.send(LaunchpadAction::ChangeSession(new_session))
+    }
+
+    pub fn send(&mut self, action: LaunchpadAction) -> Result<(), Error> {
+        self.bus.incoming.send(Action::Action(action))?;
         Ok(())
     }
 
@@ -124,6 +126,7 @@ impl TestStateInner {
                     state.apply(delta);
                 }
             },
+            Reaction::TransferResult(_) => {},
         }
         Ok(())
     }
@@ -0,0 +1,96 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+mod common;
+
+use anyhow::Error;
+use common::TestStateInner;
+use tari_launchpad_protocol::{container::TaskStatus, launchpad::LaunchpadAction};
+use tari_sdm::ids::{ManagedTask, TaskId};
+use tari_sdm_launchpad::resources::images;
+
+#[tokio::test]
+async fn test_sdm_adopt() -> Result<(), Error> {
+    let tor = vec![images::Tor::id()];
+
+    let mut first = TestStateInner::setup(600)?;
+    wait_state(&mut first).await?;
+    leave_running(&mut first, true)?;
+    first.change_session(|session| session.tor_active = true)?;
+    while !first.check_containers(&tor, TaskStatus::is_active) {
+        first.step().await?;
+    }
+    first.send(LaunchpadAction::Shutdown)?;
+    // The state is sent after the containers are detached
+    first.state = None;
+    first.send(LaunchpadAction::Connect)?;
+    wait_state(&mut first).await?;
+
+    let mut second = TestStateInner::setup(600)?;
+    wait_state(&mut second).await?;
+    let restored = second
+        .state
+        .as_ref()
+        .is_some_and(|state| state.config.session.tor_active);
+    assert!(restored, "The session must be restored");
+    let mut checked = false;
+    let mut recreated = false;
+    loop {
+        second.step().await?;
+        match status(&second, &tor[0]) {
+            Some(TaskStatus::Progress(_)) => checked = true,
+            Some(TaskStatus::Inactive) => recreated |= checked,
+            Some(TaskStatus::Active) => break,
+            _ => {},
+        }
+    }
+    assert!(!recreated, "The container of Tor must be adopted");
+
+    leave_running(&mut second, false)?;
+    second.change_session(|session| session.stop_all())?;
+    while !second.check_containers(&tor, TaskStatus::is_inactive) {
+        second.step().await?;
+    }
+    Ok(())
+}
+
+async fn wait_state(inner: &mut TestStateInner) -> Result<(), Error> {
+    while inner.state.is_none() {
+        inner.step().await?;
+    }
+    Ok(())
+}
+
+fn leave_running(inner: &mut TestStateInner, value: bool) -> Result<(), Error> {
+    let settings = inner.state.as_ref().and_then(|state| state.config.settings.as_ref());
+    let mut settings = settings
+        .map(|settings| settings.saved_settings.clone())
+        .unwrap_or_default();
+    settings.leave_running = value;
+    inner.send(LaunchpadAction::SaveSettings(settings))
+}
+
+fn status(inner: &TestStateInner, id: &TaskId) -> Option<TaskStatus> {
+    let state = inner.state.as_ref()?;
+    state.containers.get(id).map(|container| container.status.clone())
+}
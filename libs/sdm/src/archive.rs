@@ -0,0 +1,70 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Moves the images to the hosts without access to a registry. The images are saved to a tarball together with a
+//! manifest that has the ids of the images, so the loaded images can be checked.
+
+use std::path::Path;
+
+use anyhow::{anyhow, Error};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::driver::ContainerDriver;
+
+/// The tarball with the images in the format of `docker save`.
+pub const IMAGES_FILE: &str = "images.tar";
+/// The list of the exported images.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageRecord {
+    pub name: String,
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImagesManifest {
+    pub images: Vec<ImageRecord>,
+}
+
+/// Saves the `images` and the manifest to the directory. All the images have to be pulled before.
+pub async fn export_images<D: ContainerDriver>(
+    driver: &D,
+    images: &[String],
+    dir: &Path,
+) -> Result<ImagesManifest, Error> {
+    let mut manifest = ImagesManifest::default();
+    for name in images {
+        let id = driver
+            .image_id(name)
+            .await
+            .ok_or_else(|| anyhow!("The image {} is not pulled", name))?;
+        let record = ImageRecord { name: name.clone(), id };
+        manifest.images.push(record);
+    }
+    fs::create_dir_all(dir).await?;
+    driver.save_images(images, &dir.join(IMAGES_FILE)).await?;
+    let data = serde_json::to_vec_pretty(&manifest)?;
+    fs::write(dir.join(MANIFEST_FILE), data).await?;
+    Ok(manifest)
+}
+
+/// Loads the images exported by [`export_images`] and checks that every image of the manifest has the same id.
+pub async fn import_images<D: ContainerDriver>(driver: &D, dir: &Path) -> Result<ImagesManifest, Error> {
+    let data = fs::read(dir.join(MANIFEST_FILE)).await?;
+    let manifest: ImagesManifest = serde_json::from_slice(&data)?;
+    driver.load_images(&dir.join(IMAGES_FILE)).await?;
+    for record in &manifest.images {
+        let id = driver.image_id(&record.name).await;
+        if id.as_deref() != Some(record.id.as_str()) {
+            return Err(anyhow!(
+                "The image {} doesn't match the manifest: expected {}, found {:?}",
+                record.name,
+                record.id,
+                id
+            ));
+        }
+    }
+    Ok(manifest)
+}
@@ -0,0 +1,765 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{collections::HashMap, env, path::Path, time::Duration};
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use bollard::{
+    container::{
+        Config, CreateContainerOptions, LogOutput, LogsOptions, NetworkingConfig, RemoveContainerOptions,
+        Stats as BollardStats, StatsOptions, StopContainerOptions,
+    },
+    errors::Error as BollardError,
+    image::{CreateImageOptions, ImportImageOptions, RemoveImageOptions},
+    models::{
+        ContainerInspectResponse, CreateImageInfo, EndpointSettings, EventMessage, EventMessageTypeEnum,
+        HealthStatusEnum, HostConfig, Mount as BollardMount, MountTypeEnum, PortBinding, PortMap,
+    },
+    network::{CreateNetworkOptions, InspectNetworkOptions},
+    system::EventsOptions,
+    volume::{CreateVolumeOptions, RemoveVolumeOptions},
+    Docker, API_DEFAULT_VERSION,
+};
+use chrono::Local;
+use futures::{future, stream, StreamExt, TryStreamExt};
+use hyper::Body;
+use tari_launchpad_protocol::{container::StatsData, settings::ContainerEndpoint};
+use tokio::{fs::File, io::AsyncWriteExt, net::TcpListener};
+use tokio_util::io::ReaderStream;
+
+use super::{
+    ContainerDriver, ContainerSpec, ContainerState, DriverStream, HealthState, MountSpec, PortExposure, PortSpec,
+    PullProgress, ResourceEvent, ResourceKind, SCOPE_LABEL,
+};
+use crate::registry::RegistryClient;
+
+/// Seconds to wait for a response of the daemon.
+const TIMEOUT: u64 = 120;
+
+/// The implementation of the Docker API the driver talks to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiFlavour {
+    Docker,
+    /// The Docker compatible API of Podman.
+    Podman,
+}
+
+/// The driver that manages containers with a Docker daemon or with Podman.
+#[derive(Clone)]
+pub struct DockerDriver {
+    docker: Docker,
+    flavour: ApiFlavour,
+    /// The daemon runs on this host, so the ports can be checked.
+    local: bool,
+}
+
+impl DockerDriver {
+    pub async fn connect(endpoint: &ContainerEndpoint) -> Result<Self, Error> {
+        let docker = match endpoint {
+            ContainerEndpoint::Local => Docker::connect_with_local_defaults()?,
+            ContainerEndpoint::Env => connect_with_env()?,
+            ContainerEndpoint::Socket { path } => {
+                Docker::connect_with_socket(&path.to_string_lossy(), TIMEOUT, API_DEFAULT_VERSION)?
+            },
+            ContainerEndpoint::Tcp { address } => Docker::connect_with_http(address, TIMEOUT, API_DEFAULT_VERSION)?,
+            ContainerEndpoint::Tls { address, cert_path } => connect_with_tls(address, cert_path)?,
+        };
+        let flavour = detect_flavour(&docker).await;
+        log::info!("Connected to {:?} with the {:?} API", endpoint, flavour);
+        let local = endpoint.is_local();
+        Ok(Self { docker, flavour, local })
+    }
+
+    pub fn flavour(&self) -> ApiFlavour {
+        self.flavour
+    }
+}
+
+fn connect_with_env() -> Result<Docker, Error> {
+    let Ok(host) = env::var("DOCKER_HOST") else {
+        return Ok(Docker::connect_with_local_defaults()?);
+    };
+    if host.starts_with("tcp://") || host.starts_with("http://") {
+        let tls_verify = env::var("DOCKER_TLS_VERIFY")
+            .map(|value| !value.is_empty())
+            .unwrap_or_default();
+        if tls_verify {
+            let cert_path = env::var("DOCKER_CERT_PATH").map_err(|_| anyhow!("DOCKER_CERT_PATH is not set"))?;
+            connect_with_tls(&host, Path::new(&cert_path))
+        } else {
+            Ok(Docker::connect_with_http(&host, TIMEOUT, API_DEFAULT_VERSION)?)
+        }
+    } else {
+        let path = host
+            .strip_prefix("unix://")
+            .or_else(|| host.strip_prefix("npipe://"))
+            .ok_or_else(|| anyhow!("Unsupported DOCKER_HOST: {}", host))?;
+        Ok(Docker::connect_with_socket(path, TIMEOUT, API_DEFAULT_VERSION)?)
+    }
+}
+
+fn connect_with_tls(address: &str, cert_path: &Path) -> Result<Docker, Error> {
+    let docker = Docker::connect_with_ssl(
+        address,
+        &cert_path.join("key.pem"),
+        &cert_path.join("cert.pem"),
+        &cert_path.join("ca.pem"),
+        TIMEOUT,
+        API_DEFAULT_VERSION,
+    )?;
+    Ok(docker)
+}
+
+/// Podman reports itself as a component of the version info.
+async fn detect_flavour(docker: &Docker) -> ApiFlavour {
+    match docker.version().await {
+        Ok(version) => {
+            let is_podman = version
+                .components
+                .unwrap_or_default()
+                .iter()
+                .any(|component| component.name.contains("Podman"));
+            if is_podman {
+                ApiFlavour::Podman
+            } else {
+                ApiFlavour::Docker
+            }
+        },
+        Err(err) => {
+            log::warn!("Can't detect the API flavour, Docker is assumed: {}", err);
+            ApiFlavour::Docker
+        },
+    }
+}
+
+#[async_trait]
+impl ContainerDriver for DockerDriver {
+    fn events(&self, scope: &str) -> DriverStream<ResourceEvent> {
+        let flavour = self.flavour;
+        let mut container_filter = kinds_filter(&[ResourceKind::Container]);
+        container_filter.insert("label".to_string(), vec![format!("{}={}", SCOPE_LABEL, scope)]);
+        let containers = self
+            .docker
+            .events(Some(events_options(container_filter)))
+            .filter_map(move |res| future::ready(event_conv(flavour, res)));
+        // Docker can filter networks and volumes by the exact names only
+        let prefix = format!("{}_", scope);
+        let others_filter = kinds_filter(&[ResourceKind::Network, ResourceKind::Volume]);
+        let others = self
+            .docker
+            .events(Some(events_options(others_filter)))
+            .filter_map(move |res| future::ready(event_conv(flavour, res)))
+            .try_filter(move |event| future::ready(event.name.starts_with(&prefix)));
+        stream::select(containers, others).boxed()
+    }
+
+    async fn image_exists(&self, image: &str) -> bool {
+        self.docker.inspect_image(image).await.is_ok()
+    }
+
+    fn pull_image(&self, image: &str) -> DriverStream<PullProgress> {
+        let opts = Some(CreateImageOptions {
+            from_image: image.to_string(),
+            ..Default::default()
+        });
+        self.docker
+            .create_image(opts, None, None)
+            .map_ok(progress_conv)
+            .map_err(Error::from)
+            .boxed()
+    }
+
+    async fn remove_image(&self, image: &str) -> Result<(), Error> {
+        let opts = Some(RemoveImageOptions {
+            force: true,
+            ..Default::default()
+        });
+        self.docker.remove_image(image, opts, None).await?;
+        Ok(())
+    }
+
+    async fn image_digests(&self, image: &str) -> Vec<String> {
+        let repo_digests = self
+            .docker
+            .inspect_image(image)
+            .await
+            .ok()
+            .and_then(|inspect| inspect.repo_digests)
+            .unwrap_or_default();
+        // The values have the `repository@digest` format
+        repo_digests
+            .iter()
+            .filter_map(|value| value.split_once('@'))
+            .map(|(_, digest)| digest.to_string())
+            .collect()
+    }
+
+    async fn image_id(&self, image: &str) -> Option<String> {
+        self.docker
+            .inspect_image(image)
+            .await
+            .ok()
+            .and_then(|inspect| inspect.id)
+    }
+
+    async fn save_images(&self, images: &[String], path: &Path) -> Result<(), Error> {
+        let names: Vec<&str> = images.iter().map(String::as_str).collect();
+        let mut stream = self.docker.export_images(&names).boxed();
+        let mut file = File::create(path).await?;
+        while let Some(chunk) = stream.next().await {
+            file.write_all(&chunk?).await?;
+        }
+        file.flush().await?;
+        Ok(())
+    }
+
+    async fn load_images(&self, path: &Path) -> Result<(), Error> {
+        let file = File::open(path).await?;
+        let body = Body::wrap_stream(ReaderStream::new(file));
+        let opts = ImportImageOptions { quiet: true };
+        let mut stream = self.docker.import_image(opts, body, None).boxed();
+        while let Some(info) = stream.next().await {
+            if let Some(err) = info?.error {
+                return Err(anyhow!("Can't load the images: {}", err));
+            }
+        }
+        Ok(())
+    }
+
+    async fn ping(&self) -> Result<(), Error> {
+        self.docker.ping().await?;
+        Ok(())
+    }
+
+    async fn host_cpus(&self) -> Option<usize> {
+        let ncpu = self.docker.info().await.ok()?.ncpu?;
+        usize::try_from(ncpu).ok()
+    }
+
+    async fn is_port_free(&self, port: u16) -> bool {
+        if !self.local {
+            // The ports of a remote host can't be checked
+            return true;
+        }
+        TcpListener::bind(("0.0.0.0", port)).await.is_ok()
+    }
+
+    async fn container_state(&self, name: &str) -> ContainerState {
+        let res = self.docker.inspect_container(name, None).await;
+        match res {
+            Ok(ContainerInspectResponse { state: Some(state), .. }) => {
+                if state.running.unwrap_or_default() {
+                    ContainerState::Running
+                } else {
+                    ContainerState::NotRunning
+                }
+            },
+            Ok(_) => ContainerState::NotRunning,
+            Err(_) => ContainerState::NotFound,
+        }
+    }
+
+    async fn container_labels(&self, name: &str) -> HashMap<String, String> {
+        self.docker
+            .inspect_container(name, None)
+            .await
+            .ok()
+            .and_then(|inspect| inspect.config?.labels)
+            .unwrap_or_default()
+    }
+
+    async fn host_port(&self, name: &str, port: u16) -> Option<u16> {
+        let inspect = self.docker.inspect_container(name, None).await.ok()?;
+        let ports = inspect.network_settings?.ports?;
+        let bindings = ports.get(&format!("{}/tcp", port))?.as_ref()?;
+        bindings
+            .iter()
+            .find_map(|binding| binding.host_port.as_ref()?.parse().ok())
+    }
+
+    async fn exit_code(&self, name: &str) -> Option<i64> {
+        let response = self.docker.inspect_container(name, None).await.ok()?;
+        response.state?.exit_code
+    }
+
+    async fn health(&self, name: &str) -> HealthState {
+        let health = self
+            .docker
+            .inspect_container(name, None)
+            .await
+            .ok()
+            .and_then(|response| response.state)
+            .and_then(|state| state.health);
+        let Some(health) = health else {
+            return HealthState::NoCheck;
+        };
+        match health.status {
+            Some(HealthStatusEnum::STARTING) => HealthState::Starting,
+            Some(HealthStatusEnum::HEALTHY) => HealthState::Healthy,
+            Some(HealthStatusEnum::UNHEALTHY) => {
+                // The output of the last failed check
+                let reason = health
+                    .log
+                    .unwrap_or_default()
+                    .pop()
+                    .and_then(|result| result.output)
+                    .map(|output| output.trim().to_string())
+                    .unwrap_or_else(|| "The health check failed".into());
+                HealthState::Unhealthy(reason)
+            },
+            _ => HealthState::NoCheck,
+        }
+    }
+
+    async fn create_container(&self, name: &str, spec: ContainerSpec) -> Result<(), Error> {
+        let opts = CreateContainerOptions {
+            name: name.to_string(),
+            platform: None,
+        };
+        let config = Config {
+            image: Some(spec.image),
+            attach_stdin: Some(false),
+            attach_stdout: Some(false),
+            attach_stderr: Some(false),
+            exposed_ports: Some(exposed_ports(&spec.ports)),
+            open_stdin: Some(true),
+            stdin_once: Some(false),
+            tty: Some(true),
+            env: Some(spec.env),
+            volumes: Some(volumes_map(spec.volumes)),
+            cmd: Some(spec.cmd),
+            host_config: Some(HostConfig {
+                binds: Some(vec![]),
+                network_mode: Some("bridge".to_string()),
+                port_bindings: Some(ports_map(&spec.ports, self.flavour)),
+                mounts: Some(spec.mounts.into_iter().map(make_mount).collect()),
+                cpu_period: spec.limits.cpu_period,
+                cpu_quota: spec.limits.cpu_quota,
+                cpu_shares: spec.limits.cpu_shares,
+                cpuset_cpus: spec.limits.cpuset_cpus,
+                memory: spec.limits.memory,
+                memory_swap: spec.limits.memory_swap,
+                pids_limit: spec.limits.pids_limit,
+                ..Default::default()
+            }),
+            networking_config: Some(networks_map(spec.networks)),
+            labels: Some(spec.labels.into_iter().collect()),
+            ..Default::default()
+        };
+        self.docker.create_container(Some(opts), config).await?;
+        Ok(())
+    }
+
+    async fn start_container(&self, name: &str) -> Result<(), Error> {
+        self.docker.start_container::<String>(name, None).await?;
+        Ok(())
+    }
+
+    async fn stop_container(&self, name: &str, timeout: Duration) -> Result<(), Error> {
+        let opts = StopContainerOptions {
+            t: i64::try_from(timeout.as_secs()).unwrap_or(i64::MAX),
+        };
+        let docker = self.docker.clone();
+        let name = name.to_string();
+        // The daemon responds when the container exits, but the tasks follow the events instead
+        tokio::spawn(async move {
+            if let Err(err) = docker.stop_container(&name, Some(opts)).await {
+                log::error!("Can't stop the container {}: {}", name, err);
+            }
+        });
+        Ok(())
+    }
+
+    async fn kill_container(&self, name: &str) -> Result<(), Error> {
+        self.docker.kill_container::<String>(name, None).await?;
+        Ok(())
+    }
+
+    async fn remove_container(&self, name: &str) -> Result<(), Error> {
+        let opts = RemoveContainerOptions {
+            force: true,
+            ..Default::default()
+        };
+        self.docker.remove_container(name, Some(opts)).await?;
+        Ok(())
+    }
+
+    fn logs(&self, name: &str) -> DriverStream<String> {
+        let opts = LogsOptions::<String> {
+            follow: true,
+            stdout: true,
+            stderr: true,
+            ..Default::default()
+        };
+        self.docker.logs(name, Some(opts)).map(log_conv).boxed()
+    }
+
+    fn stats(&self, name: &str) -> DriverStream<StatsData> {
+        let opts = StatsOptions {
+            stream: true,
+            one_shot: false,
+        };
+        self.docker.stats(name, Some(opts)).map(stat_conv).boxed()
+    }
+
+    async fn network_exists(&self, name: &str) -> bool {
+        let opts = InspectNetworkOptions {
+            verbose: false,
+            scope: "local",
+        };
+        self.docker.inspect_network(name, Some(opts)).await.is_ok()
+    }
+
+    async fn create_network(&self, name: &str) -> Result<(), Error> {
+        let options = CreateNetworkOptions {
+            name,
+            check_duplicate: true,
+            driver: "bridge",
+            internal: false,
+            attachable: false,
+            ingress: false,
+            ipam: Default::default(),
+            enable_ipv6: false,
+            options: Default::default(),
+            labels: Default::default(),
+        };
+        self.docker.create_network(options).await?;
+        // TODO: Check warnings...
+        Ok(())
+    }
+
+    async fn remove_network(&self, name: &str) -> Result<(), Error> {
+        self.docker.remove_network(name).await?;
+        Ok(())
+    }
+
+    async fn volume_exists(&self, name: &str) -> bool {
+        self.docker.inspect_volume(name).await.is_ok()
+    }
+
+    async fn create_volume(&self, name: &str) -> Result<(), Error> {
+        let options = CreateVolumeOptions {
+            name,
+            ..Default::default()
+        };
+        self.docker.create_volume(options).await?;
+        // TODO: Check warnings...
+        Ok(())
+    }
+
+    async fn remove_volume(&self, name: &str) -> Result<(), Error> {
+        let opts = RemoveVolumeOptions { force: true };
+        self.docker.remove_volume(name, Some(opts)).await?;
+        Ok(())
+    }
+}
+
+/// The daemon asks the registry with its own credentials.
+#[async_trait]
+impl RegistryClient for DockerDriver {
+    async fn manifest_digest(&self, image: &str) -> Result<String, Error> {
+        let inspect = self.docker.inspect_registry_image(image, None).await?;
+        inspect
+            .descriptor
+            .digest
+            .ok_or_else(|| anyhow!("The registry returned no digest of the image {}", image))
+    }
+}
+
+fn kinds_filter(kinds: &[ResourceKind]) -> HashMap<String, Vec<String>> {
+    let types = kinds.iter().map(|kind| kind.as_str().to_string()).collect();
+    let mut filter = HashMap::new();
+    filter.insert("type".to_string(), types);
+    filter
+}
+
+fn events_options(filters: HashMap<String, Vec<String>>) -> EventsOptions<String> {
+    EventsOptions {
+        since: None,
+        until: None,
+        filters,
+    }
+}
+
+fn event_conv(flavour: ApiFlavour, res: Result<EventMessage, BollardError>) -> Option<Result<ResourceEvent, Error>> {
+    let msg = match res {
+        Ok(msg) => msg,
+        Err(err) => return Some(Err(err.into())),
+    };
+    let EventMessage {
+        typ: Some(typ),
+        action: Some(action),
+        actor: Some(actor),
+        ..
+    } = msg
+    else {
+        return None;
+    };
+    let kind = match typ {
+        EventMessageTypeEnum::CONTAINER => ResourceKind::Container,
+        EventMessageTypeEnum::NETWORK => ResourceKind::Network,
+        EventMessageTypeEnum::VOLUME => ResourceKind::Volume,
+        _ => return None,
+    };
+    // The id of a volume is its name
+    let name = actor
+        .attributes
+        .and_then(|mut attributes| attributes.remove("name"))
+        .or(actor.id)?;
+    let action = match flavour {
+        ApiFlavour::Docker => action,
+        ApiFlavour::Podman => podman_action(action),
+    };
+    Some(Ok(ResourceEvent { kind, name, action }))
+}
+
+/// Podman uses its own names for some actions. They are renamed to the Docker ones the tasks expect.
+fn podman_action(action: String) -> String {
+    match action.as_ref() {
+        "died" => "die".to_string(),
+        "remove" => "destroy".to_string(),
+        _ => action,
+    }
+}
+
+fn progress_conv(info: CreateImageInfo) -> PullProgress {
+    log::debug!("Created Image Info: {:?}", info);
+    let details = info.progress_detail.unwrap_or_default();
+    PullProgress {
+        id: info.id,
+        status: info.status,
+        current: details.current,
+        total: details.total,
+    }
+}
+
+type BollardMap = HashMap<String, HashMap<(), ()>>;
+
+fn volumes_map(volumes: Vec<String>) -> BollardMap {
+    let mut result = BollardMap::new();
+    for volume in volumes {
+        result.insert(volume, HashMap::default());
+    }
+    result
+}
+
+fn exposed_ports(ports: &[PortSpec]) -> BollardMap {
+    let mut result = BollardMap::new();
+    for port in ports {
+        result.insert(format!("{}/tcp", port.container), HashMap::default());
+    }
+    result
+}
+
+fn ports_map(ports: &[PortSpec], flavour: ApiFlavour) -> PortMap {
+    let mut result = PortMap::new();
+    for port in ports.iter().filter(|port| port.is_published()) {
+        let k = format!("{}/tcp", port.container);
+        // Podman doesn't treat an empty address as any, so the addresses are explicit
+        let host_ip = match port.exposure {
+            PortExposure::Lan => "0.0.0.0",
+            PortExposure::Loopback | PortExposure::None => "127.0.0.1",
+        };
+        let binding = match flavour {
+            ApiFlavour::Docker => PortBinding {
+                host_ip: Some(host_ip.to_string()),
+                host_port: Some(format!("{}/tcp", port.host)),
+            },
+            // Podman accepts a number only
+            ApiFlavour::Podman => PortBinding {
+                host_ip: Some(host_ip.to_string()),
+                host_port: Some(port.host.to_string()),
+            },
+        };
+        result.insert(k, Some(vec![binding]));
+    }
+    result
+}
+
+fn networks_map(networks: Vec<(String, String)>) -> NetworkingConfig<String> {
+    let mut endpoints = HashMap::new();
+    for (net_name, alias) in networks {
+        let endpoint = EndpointSettings {
+            aliases: Some(vec![alias]),
+            ..Default::default()
+        };
+        endpoints.insert(net_name, endpoint);
+    }
+    NetworkingConfig {
+        endpoints_config: endpoints,
+    }
+}
+
+fn make_mount(mount: MountSpec) -> BollardMount {
+    match mount {
+        MountSpec::Volume { source, target } => BollardMount {
+            target: Some(target),
+            source: Some(source),
+            typ: Some(MountTypeEnum::VOLUME),
+            volume_options: None,
+            ..Default::default()
+        },
+        MountSpec::Bind { source, target } => BollardMount {
+            target: Some(target),
+            source: Some(canonicalize(source)),
+            typ: Some(MountTypeEnum::BIND),
+            bind_options: None,
+            ..Default::default()
+        },
+    }
+}
+
+fn log_conv(res: Result<LogOutput, BollardError>) -> Result<String, Error> {
+    if let Ok(LogOutput::Console { message }) = res {
+        match std::str::from_utf8(message.as_ref()) {
+            Ok(data) => {
+                let message = data.to_string();
+                log::trace!("Log: {}", message);
+                Ok(message)
+            },
+            Err(err) => Err(anyhow!("Can't parse log text: {}", err)),
+        }
+    } else {
+        Err(anyhow!("Unsupported log event: {:?}", res))
+    }
+}
+
+fn stat_conv(res: Result<BollardStats, BollardError>) -> Result<StatsData, Error> {
+    if let Ok(BollardStats {
+        cpu_stats,
+        memory_stats,
+        ..
+    }) = res
+    {
+        Ok(StatsData {
+            timestamp: Local::now().naive_local(),
+            system_cpu_usage: cpu_stats.system_cpu_usage.unwrap_or_default() as i64,
+            cpu_usage: cpu_stats.cpu_usage.total_usage as i64,
+            mem_limit: memory_stats.limit.unwrap_or_default().into(),
+            mem_usage: memory_stats.usage.unwrap_or_default().into(),
+        })
+    } else {
+        Err(anyhow!("Unsupported stats event: {:?}", res))
+    }
+}
+
+// FIXME: This might be replaceable by std::fs::canonicalize, but I don't have a windows machine to check
+fn canonicalize<P: AsRef<Path>>(path: P) -> String {
+    #[cfg(target_os = "windows")]
+    let path = path.as_ref().to_string_lossy().to_string();
+    #[cfg(target_os = "macos")]
+    let path = format!("/host_mnt{}", path.as_ref().to_string_lossy());
+    #[cfg(target_os = "linux")]
+    let path = path.as_ref().to_string_lossy().to_string();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use bollard::models::EventActor;
+
+    use super::*;
+
+    fn event(typ: EventMessageTypeEnum, name: &str, action: &str) -> Result<EventMessage, BollardError> {
+        let attributes = [("name".to_string(), name.to_string())].into_iter().collect();
+        Ok(EventMessage {
+            typ: Some(typ),
+            action: Some(action.to_string()),
+            actor: Some(EventActor {
+                id: None,
+                attributes: Some(attributes),
+            }),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_podman_actions_renamed() {
+        let res = event(EventMessageTypeEnum::CONTAINER, "scope_app", "died");
+        let event = event_conv(ApiFlavour::Podman, res).unwrap().unwrap();
+        assert_eq!(event.kind, ResourceKind::Container);
+        assert_eq!(event.name, "scope_app");
+        assert_eq!(event.action, "die");
+
+        let res = event(EventMessageTypeEnum::NETWORK, "scope_network", "remove");
+        let event = event_conv(ApiFlavour::Podman, res).unwrap().unwrap();
+        assert_eq!(event.kind, ResourceKind::Network);
+        assert_eq!(event.action, "destroy");
+
+        let res = event(EventMessageTypeEnum::CONTAINER, "scope_app", "died");
+        let event = event_conv(ApiFlavour::Docker, res).unwrap().unwrap();
+        assert_eq!(event.action, "died");
+    }
+
+    #[test]
+    fn test_other_types_skipped() {
+        let res = event(EventMessageTypeEnum::IMAGE, "scope_app", "pull");
+        assert!(event_conv(ApiFlavour::Docker, res).is_none());
+    }
+
+    #[test]
+    fn test_volume_named_by_id() {
+        let res = Ok(EventMessage {
+            typ: Some(EventMessageTypeEnum::VOLUME),
+            action: Some("destroy".to_string()),
+            actor: Some(EventActor {
+                id: Some("scope_volume".to_string()),
+                attributes: Some([("driver".to_string(), "local".to_string())].into_iter().collect()),
+            }),
+            ..Default::default()
+        });
+        let event = event_conv(ApiFlavour::Docker, res).unwrap().unwrap();
+        assert_eq!(event.kind, ResourceKind::Volume);
+        assert_eq!(event.name, "scope_volume");
+    }
+
+    #[test]
+    fn test_podman_port_bindings() {
+        let port = PortSpec {
+            container: 18142,
+            host: 18142,
+            exposure: PortExposure::Lan,
+        };
+        let map = ports_map(&[port], ApiFlavour::Podman);
+        let binding = map["18142/tcp"].as_ref().unwrap()[0].clone();
+        assert_eq!(binding.host_port.as_deref(), Some("18142"));
+        assert_eq!(binding.host_ip.as_deref(), Some("0.0.0.0"));
+
+        let map = ports_map(&[port], ApiFlavour::Docker);
+        let binding = map["18142/tcp"].as_ref().unwrap()[0].clone();
+        assert_eq!(binding.host_port.as_deref(), Some("18142/tcp"));
+    }
+
+    #[test]
+    fn test_forwarded_port_bindings() {
+        let port = PortSpec {
+            container: 18142,
+            host: 28142,
+            exposure: PortExposure::default(),
+        };
+        let map = ports_map(&[port], ApiFlavour::Podman);
+        let binding = map["18142/tcp"].as_ref().unwrap()[0].clone();
+        assert_eq!(binding.host_port.as_deref(), Some("28142"));
+        assert!(exposed_ports(&[port]).contains_key("18142/tcp"));
+    }
+
+    #[test]
+    fn test_port_exposure() {
+        let port = PortSpec {
+            container: 9051,
+            host: 9051,
+            exposure: PortExposure::Loopback,
+        };
+        let map = ports_map(&[port], ApiFlavour::Docker);
+        let binding = map["9051/tcp"].as_ref().unwrap()[0].clone();
+        assert_eq!(binding.host_ip.as_deref(), Some("127.0.0.1"));
+
+        let hidden = PortSpec {
+            exposure: PortExposure::None,
+            ..port
+        };
+        assert!(ports_map(&[hidden], ApiFlavour::Docker).is_empty());
+        assert!(exposed_ports(&[hidden]).contains_key("9051/tcp"));
+    }
+}
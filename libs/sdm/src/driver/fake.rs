@@ -0,0 +1,499 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use futures::{future, stream, StreamExt};
+use tari_launchpad_protocol::container::StatsData;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+
+use super::{
+    ContainerDriver, ContainerSpec, ContainerState, DriverStream, HealthState, PullProgress, ResourceEvent,
+    ResourceKind,
+};
+use crate::registry::RegistryClient;
+
+#[derive(Default)]
+struct FakeState {
+    images: HashSet<String>,
+    /// The digests of the manifests the local images were pulled with.
+    digests: HashMap<String, String>,
+    /// The digests of the manifests in the registry.
+    manifests: HashMap<String, String>,
+    /// Images that can't be pulled.
+    unavailable: HashSet<String>,
+    /// Containers that ignore `SIGTERM`.
+    stubborn: HashSet<String>,
+    /// The ports of the host taken by other processes.
+    busy_ports: HashSet<u16>,
+    /// Containers that can't be created.
+    broken: HashSet<String>,
+    /// The number of the attempts to create every container.
+    create_attempts: HashMap<String, usize>,
+    containers: HashMap<String, FakeContainer>,
+    networks: HashSet<String>,
+    volumes: HashSet<String>,
+    /// All the events in the order they happened.
+    history: Vec<ResourceEvent>,
+    /// The number of the opened events streams.
+    subscriptions: usize,
+    /// The daemon doesn't respond.
+    offline: bool,
+}
+
+struct FakeContainer {
+    spec: ContainerSpec,
+    running: bool,
+    exit_code: Option<i64>,
+    health: HealthState,
+}
+
+/// An in-memory runtime. It changes the state immediately and reports the same events as Docker does, that allows
+/// to check the state machines of the tasks without a daemon.
+#[derive(Clone)]
+pub struct FakeDriver {
+    state: Arc<Mutex<FakeState>>,
+    /// It's replaced to close the opened streams.
+    events: Arc<Mutex<broadcast::Sender<ResourceEvent>>>,
+}
+
+impl Default for FakeDriver {
+    fn default() -> Self {
+        let (events, _) = broadcast::channel(64);
+        Self {
+            state: Arc::default(),
+            events: Arc::new(Mutex::new(events)),
+        }
+    }
+}
+
+impl FakeDriver {
+    /// Adds an image as if it was pulled before.
+    pub fn add_image(&self, image: impl ToString) {
+        self.lock().images.insert(image.to_string());
+    }
+
+    /// Pushes a new version of the image to the registry. The next pull uses it.
+    pub fn publish(&self, image: impl ToString, digest: impl ToString) {
+        self.lock().manifests.insert(image.to_string(), digest.to_string());
+    }
+
+    /// Makes all the attempts to pull the image fail.
+    pub fn make_unavailable(&self, image: impl ToString) {
+        self.lock().unavailable.insert(image.to_string());
+    }
+
+    /// Makes the container ignore the stop requests, so it can only be killed.
+    pub fn ignore_stop(&self, name: impl ToString) {
+        self.lock().stubborn.insert(name.to_string());
+    }
+
+    /// Takes the port of the host as if another process listens it.
+    pub fn occupy_port(&self, port: u16) {
+        self.lock().busy_ports.insert(port);
+    }
+
+    pub fn release_port(&self, port: u16) {
+        self.lock().busy_ports.remove(&port);
+    }
+
+    /// Makes all the attempts to create the container fail.
+    pub fn break_create(&self, name: impl ToString) {
+        self.lock().broken.insert(name.to_string());
+    }
+
+    pub fn create_attempts(&self, name: &str) -> usize {
+        self.lock().create_attempts.get(name).copied().unwrap_or_default()
+    }
+
+    /// Makes the running container exit by itself.
+    pub fn crash(&self, name: &str, exit_code: i64) {
+        let crashed = self
+            .lock()
+            .containers
+            .get_mut(name)
+            .filter(|container| container.running)
+            .map(|container| {
+                container.running = false;
+                container.exit_code = Some(exit_code);
+            })
+            .is_some();
+        if crashed {
+            self.notify(ResourceKind::Container, name, "die");
+        }
+    }
+
+    /// Sets the result of the `HEALTHCHECK` of the container.
+    pub fn set_health(&self, name: &str, health: HealthState) {
+        if let Some(container) = self.lock().containers.get_mut(name) {
+            container.health = health;
+        }
+    }
+
+    pub fn has_image(&self, image: &str) -> bool {
+        self.lock().images.contains(image)
+    }
+
+    pub fn container_spec(&self, name: &str) -> Option<ContainerSpec> {
+        self.lock().containers.get(name).map(|container| container.spec.clone())
+    }
+
+    pub fn is_running(&self, name: &str) -> bool {
+        self.lock()
+            .containers
+            .get(name)
+            .map(|container| container.running)
+            .unwrap_or_default()
+    }
+
+    pub fn has_network(&self, name: &str) -> bool {
+        self.lock().networks.contains(name)
+    }
+
+    pub fn has_volume(&self, name: &str) -> bool {
+        self.lock().volumes.contains(name)
+    }
+
+    pub fn history(&self) -> Vec<ResourceEvent> {
+        self.lock().history.clone()
+    }
+
+    pub fn event_subscriptions(&self) -> usize {
+        self.lock().subscriptions
+    }
+
+    /// Closes all the events streams as if the connection to the daemon was lost.
+    pub fn interrupt_events(&self) {
+        let (events, _) = broadcast::channel(64);
+        *self.events_sender() = events;
+    }
+
+    /// Stops the daemon. The state of the resources is kept, but the events are lost until it's back.
+    pub fn go_offline(&self) {
+        self.lock().offline = true;
+        self.interrupt_events();
+    }
+
+    pub fn go_online(&self) {
+        self.lock().offline = false;
+    }
+
+    fn events_sender(&self) -> MutexGuard<'_, broadcast::Sender<ResourceEvent>> {
+        self.events.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FakeState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn notify(&self, kind: ResourceKind, name: &str, action: &str) {
+        let event = ResourceEvent {
+            kind,
+            name: name.to_string(),
+            action: action.to_string(),
+        };
+        self.lock().history.push(event.clone());
+        // No subscribers is not an error
+        self.events_sender().send(event).ok();
+    }
+}
+
+#[async_trait]
+impl ContainerDriver for FakeDriver {
+    fn events(&self, scope: &str) -> DriverStream<ResourceEvent> {
+        self.lock().subscriptions += 1;
+        // The containers are named with the scope prefix as well, so the labels are not checked
+        let prefix = format!("{}_", scope);
+        BroadcastStream::new(self.events_sender().subscribe())
+            .filter_map(move |res| {
+                let event = res.ok().filter(|event| event.name.starts_with(&prefix));
+                future::ready(event.map(Ok))
+            })
+            .boxed()
+    }
+
+    async fn image_exists(&self, image: &str) -> bool {
+        self.has_image(image)
+    }
+
+    fn pull_image(&self, image: &str) -> DriverStream<PullProgress> {
+        let mut state = self.lock();
+        if state.unavailable.contains(image) {
+            let err = anyhow!("Image {} is not available", image);
+            return stream::once(future::ready(Err(err))).boxed();
+        }
+        state.images.insert(image.to_string());
+        if let Some(digest) = state.manifests.get(image).cloned() {
+            state.digests.insert(image.to_string(), digest);
+        }
+        // A single layer with the id of the image
+        let downloading = PullProgress {
+            id: Some(image.to_string()),
+            status: Some("Downloading".into()),
+            current: Some(1),
+            total: Some(1),
+        };
+        let complete = PullProgress {
+            id: Some(image.to_string()),
+            status: Some("Pull complete".into()),
+            ..Default::default()
+        };
+        stream::iter([Ok(downloading), Ok(complete)]).boxed()
+    }
+
+    async fn remove_image(&self, image: &str) -> Result<(), Error> {
+        let mut state = self.lock();
+        state.digests.remove(image);
+        if state.images.remove(image) {
+            Ok(())
+        } else {
+            Err(anyhow!("No such image: {}", image))
+        }
+    }
+
+    async fn image_digests(&self, image: &str) -> Vec<String> {
+        self.lock().digests.get(image).cloned().into_iter().collect()
+    }
+
+    async fn image_id(&self, image: &str) -> Option<String> {
+        // The ids of the fake images are derived from the names
+        self.has_image(image).then(|| format!("sha256:{}", image))
+    }
+
+    async fn save_images(&self, images: &[String], path: &Path) -> Result<(), Error> {
+        if let Some(missing) = images.iter().find(|image| !self.has_image(image)) {
+            return Err(anyhow!("Image {} doesn't exist", missing));
+        }
+        // The fake tarball is the list of the images
+        tokio::fs::write(path, images.join("\n")).await?;
+        Ok(())
+    }
+
+    async fn load_images(&self, path: &Path) -> Result<(), Error> {
+        let data = tokio::fs::read_to_string(path).await?;
+        let mut state = self.lock();
+        for image in data.lines() {
+            state.images.insert(image.to_string());
+        }
+        Ok(())
+    }
+
+    async fn ping(&self) -> Result<(), Error> {
+        if self.lock().offline {
+            Err(anyhow!("The daemon is not responding"))
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn host_cpus(&self) -> Option<usize> {
+        // The fake host doesn't limit the containers
+        None
+    }
+
+    async fn is_port_free(&self, port: u16) -> bool {
+        !self.lock().busy_ports.contains(&port)
+    }
+
+    async fn container_state(&self, name: &str) -> ContainerState {
+        match self.lock().containers.get(name) {
+            Some(container) if container.running => ContainerState::Running,
+            Some(_) => ContainerState::NotRunning,
+            None => ContainerState::NotFound,
+        }
+    }
+
+    async fn container_labels(&self, name: &str) -> HashMap<String, String> {
+        self.lock()
+            .containers
+            .get(name)
+            .map(|container| container.spec.labels.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    async fn host_port(&self, name: &str, port: u16) -> Option<u16> {
+        let state = self.lock();
+        let container = state.containers.get(name).filter(|container| container.running)?;
+        container
+            .spec
+            .ports
+            .iter()
+            .find(|spec| spec.container == port && spec.is_published())
+            .map(|spec| spec.host)
+    }
+
+    async fn exit_code(&self, name: &str) -> Option<i64> {
+        self.lock().containers.get(name)?.exit_code
+    }
+
+    async fn health(&self, name: &str) -> HealthState {
+        self.lock()
+            .containers
+            .get(name)
+            .map(|container| container.health.clone())
+            .unwrap_or(HealthState::NoCheck)
+    }
+
+    async fn create_container(&self, name: &str, spec: ContainerSpec) -> Result<(), Error> {
+        {
+            let mut state = self.lock();
+            *state.create_attempts.entry(name.to_string()).or_default() += 1;
+            if state.broken.contains(name) {
+                return Err(anyhow!("Can't create the container {}", name));
+            }
+            if !state.images.contains(&spec.image) {
+                return Err(anyhow!("No such image: {}", spec.image));
+            }
+            if state.containers.contains_key(name) {
+                return Err(anyhow!("The container name {} is already in use", name));
+            }
+            let container = FakeContainer {
+                spec,
+                running: false,
+                exit_code: None,
+                health: HealthState::NoCheck,
+            };
+            state.containers.insert(name.to_string(), container);
+        }
+        self.notify(ResourceKind::Container, name, "create");
+        Ok(())
+    }
+
+    async fn start_container(&self, name: &str) -> Result<(), Error> {
+        self.lock()
+            .containers
+            .get_mut(name)
+            .ok_or_else(|| anyhow!("No such container: {}", name))?
+            .running = true;
+        self.notify(ResourceKind::Container, name, "start");
+        Ok(())
+    }
+
+    async fn stop_container(&self, name: &str, _timeout: Duration) -> Result<(), Error> {
+        let stubborn = {
+            let mut state = self.lock();
+            let stubborn = state.stubborn.contains(name);
+            let container = state
+                .containers
+                .get_mut(name)
+                .ok_or_else(|| anyhow!("No such container: {}", name))?;
+            if !container.running {
+                return Err(anyhow!("Container {} is not running", name));
+            }
+            container.running = stubborn;
+            if !stubborn {
+                container.exit_code = Some(0);
+            }
+            stubborn
+        };
+        // The signal is delivered in any case
+        self.notify(ResourceKind::Container, name, "kill");
+        if !stubborn {
+            self.notify(ResourceKind::Container, name, "die");
+            self.notify(ResourceKind::Container, name, "stop");
+        }
+        Ok(())
+    }
+
+    async fn kill_container(&self, name: &str) -> Result<(), Error> {
+        {
+            let mut state = self.lock();
+            let container = state
+                .containers
+                .get_mut(name)
+                .ok_or_else(|| anyhow!("No such container: {}", name))?;
+            if !container.running {
+                return Err(anyhow!("Container {} is not running", name));
+            }
+            container.running = false;
+            container.exit_code = Some(137);
+        }
+        self.notify(ResourceKind::Container, name, "kill");
+        self.notify(ResourceKind::Container, name, "die");
+        Ok(())
+    }
+
+    async fn remove_container(&self, name: &str) -> Result<(), Error> {
+        let container = self
+            .lock()
+            .containers
+            .remove(name)
+            .ok_or_else(|| anyhow!("No such container: {}", name))?;
+        // The removal is forced, so a running container is killed first
+        if container.running {
+            self.notify(ResourceKind::Container, name, "kill");
+            self.notify(ResourceKind::Container, name, "die");
+        }
+        self.notify(ResourceKind::Container, name, "destroy");
+        Ok(())
+    }
+
+    fn logs(&self, _name: &str) -> DriverStream<String> {
+        // Containers are silent, but the stream has to stay open as the Docker one does
+        stream::pending().boxed()
+    }
+
+    fn stats(&self, _name: &str) -> DriverStream<StatsData> {
+        stream::pending().boxed()
+    }
+
+    async fn network_exists(&self, name: &str) -> bool {
+        self.has_network(name)
+    }
+
+    async fn create_network(&self, name: &str) -> Result<(), Error> {
+        if !self.lock().networks.insert(name.to_string()) {
+            return Err(anyhow!("Network {} already exists", name));
+        }
+        self.notify(ResourceKind::Network, name, "create");
+        Ok(())
+    }
+
+    async fn remove_network(&self, name: &str) -> Result<(), Error> {
+        if !self.lock().networks.remove(name) {
+            return Err(anyhow!("No such network: {}", name));
+        }
+        self.notify(ResourceKind::Network, name, "destroy");
+        Ok(())
+    }
+
+    async fn volume_exists(&self, name: &str) -> bool {
+        self.has_volume(name)
+    }
+
+    async fn create_volume(&self, name: &str) -> Result<(), Error> {
+        if !self.lock().volumes.insert(name.to_string()) {
+            return Err(anyhow!("Volume {} already exists", name));
+        }
+        self.notify(ResourceKind::Volume, name, "create");
+        Ok(())
+    }
+
+    async fn remove_volume(&self, name: &str) -> Result<(), Error> {
+        if !self.lock().volumes.remove(name) {
+            return Err(anyhow!("No such volume: {}", name));
+        }
+        self.notify(ResourceKind::Volume, name, "destroy");
+        Ok(())
+    }
+}
+
+/// The registry of the fake runtime. The manifests are added by `FakeDriver::publish`.
+#[async_trait]
+impl RegistryClient for FakeDriver {
+    async fn manifest_digest(&self, image: &str) -> Result<String, Error> {
+        self.lock()
+            .manifests
+            .get(image)
+            .cloned()
+            .ok_or_else(|| anyhow!("No such manifest: {}", image))
+    }
+}
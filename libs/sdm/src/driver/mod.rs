@@ -0,0 +1,353 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! The container runtime abstraction. The tasks never talk to a runtime directly and use a [`ContainerDriver`]
+//! instead, that allows to run the state machines against the Docker daemon or against the in-memory [`FakeDriver`].
+
+mod docker;
+mod fake;
+
+use std::{collections::HashMap, fmt, path::Path, time::Duration};
+
+use anyhow::Error;
+use async_trait::async_trait;
+pub use docker::{ApiFlavour, DockerDriver};
+pub use fake::FakeDriver;
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tari_launchpad_protocol::container::StatsData;
+
+use crate::forwarder::Converter;
+
+pub type DriverStream<T> = BoxStream<'static, Result<T, Error>>;
+
+/// The label with the scope of the container. The networks and the volumes have no labels in the events, so they are
+/// recognized by the scope prefix of their names.
+pub const SCOPE_LABEL: &str = "com.tari.launchpad.scope";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    Container,
+    Network,
+    Volume,
+}
+
+impl ResourceKind {
+    /// The name of the kind used by the Docker API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Container => "container",
+            Self::Network => "network",
+            Self::Volume => "volume",
+        }
+    }
+}
+
+impl fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A change of a resource reported by the runtime.
+#[derive(Debug, Clone)]
+pub struct ResourceEvent {
+    pub kind: ResourceKind,
+    pub name: String,
+    /// The action in the Docker terms: `create`, `start`, `kill`, `die`, `stop`, `destroy`.
+    pub action: String,
+}
+
+/// A progress record of pulling an image. Every layer is reported separately.
+#[derive(Debug, Clone, Default)]
+pub struct PullProgress {
+    /// The id of a layer.
+    pub id: Option<String>,
+    pub status: Option<String>,
+    pub current: Option<i64>,
+    pub total: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerState {
+    Running,
+    NotRunning,
+    NotFound,
+}
+
+/// The status of the `HEALTHCHECK` of a container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthState {
+    /// The image doesn't define a health check.
+    NoCheck,
+    Starting,
+    Healthy,
+    Unhealthy(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum MountSpec {
+    Volume { source: String, target: String },
+    Bind { source: String, target: String },
+}
+
+/// Everything required to create a container. All the resources are referenced by the names in the runtime.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize)]
+pub struct ContainerSpec {
+    pub image: String,
+    pub cmd: Vec<String>,
+    pub env: Vec<String>,
+    pub ports: Vec<PortSpec>,
+    pub volumes: Vec<String>,
+    /// Pairs of a network name and an alias of the container in that network.
+    pub networks: Vec<(String, String)>,
+    pub mounts: Vec<MountSpec>,
+    pub limits: ResourceLimits,
+    /// The metadata of the container. It's not a part of the fingerprint.
+    pub labels: Vec<(String, String)>,
+}
+
+impl ContainerSpec {
+    /// The spec without the labels and with the sorted lists where the order doesn't matter.
+    pub fn canonical(&self) -> Self {
+        let mut spec = Self {
+            labels: Vec::new(),
+            ..self.clone()
+        };
+        spec.env.sort();
+        spec.ports.sort();
+        spec.volumes.sort();
+        spec.networks.sort();
+        spec.mounts.sort();
+        spec
+    }
+
+    /// A digest of the canonical spec. It's stored in a label of the container, so it has to be the same for every
+    /// build of the launchpad.
+    pub fn fingerprint(&self) -> String {
+        // The serialization of the plain structs can't fail
+        let data = serde_json::to_vec(&self.canonical()).unwrap_or_default();
+        Sha256::digest(data)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// The names of the fields that differ from the `other` spec. The labels are ignored.
+    pub fn changed_fields(&self, other: &Self) -> Vec<&'static str> {
+        let (this, other) = (self.canonical(), other.canonical());
+        let fields = [
+            ("image", this.image == other.image),
+            ("cmd", this.cmd == other.cmd),
+            ("env", this.env == other.env),
+            ("ports", this.ports == other.ports),
+            ("volumes", this.volumes == other.volumes),
+            ("networks", this.networks == other.networks),
+            ("mounts", this.mounts == other.mounts),
+            ("limits", this.limits == other.limits),
+        ];
+        fields
+            .into_iter()
+            .filter(|(_, same)| !same)
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+/// The interfaces of the host a port of a container is published on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum PortExposure {
+    /// The port is reachable from the host only.
+    #[default]
+    Loopback,
+    /// The port is reachable from other hosts.
+    Lan,
+    /// The port is not published, it's reachable from the containers of the same network only.
+    None,
+}
+
+/// A port of a container published on the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct PortSpec {
+    pub container: u16,
+    pub host: u16,
+    pub exposure: PortExposure,
+}
+
+impl PortSpec {
+    pub fn is_published(&self) -> bool {
+        self.exposure != PortExposure::None
+    }
+}
+
+/// The limits of the resources of a container in the units of the Docker API. `None` means unlimited.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize)]
+pub struct ResourceLimits {
+    /// The length of a CPU period in microseconds.
+    pub cpu_period: Option<i64>,
+    /// The CPU time in microseconds the container can use in a period.
+    pub cpu_quota: Option<i64>,
+    /// The relative weight of the container for the CPU time.
+    pub cpu_shares: Option<i64>,
+    /// The CPUs the container can run on, e.g. `0-3` or `0,1`.
+    pub cpuset_cpus: Option<String>,
+    /// The memory limit in bytes.
+    pub memory: Option<i64>,
+    /// The limit of the memory and the swap together in bytes.
+    pub memory_swap: Option<i64>,
+    pub pids_limit: Option<i64>,
+}
+
+#[async_trait]
+pub trait ContainerDriver: Clone + Send + Sync + 'static {
+    /// Subscribes to the events of the containers, networks and volumes of the `scope`. The scope shares a single
+    /// stream between the tasks.
+    fn events(&self, scope: &str) -> DriverStream<ResourceEvent>;
+
+    async fn image_exists(&self, image: &str) -> bool;
+
+    fn pull_image(&self, image: &str) -> DriverStream<PullProgress>;
+
+    async fn remove_image(&self, image: &str) -> Result<(), Error>;
+
+    /// The digests of the manifests the local image was pulled with. It's empty if the image doesn't exist.
+    async fn image_digests(&self, image: &str) -> Vec<String>;
+
+    /// The id of the local image. Unlike the digests, it's kept when the image is saved and loaded.
+    async fn image_id(&self, image: &str) -> Option<String>;
+
+    /// Saves the images to a tarball in the format of `docker save`.
+    async fn save_images(&self, images: &[String], path: &Path) -> Result<(), Error>;
+
+    /// Loads the images from a tarball created by `save_images`.
+    async fn load_images(&self, path: &Path) -> Result<(), Error>;
+
+    /// Checks that the daemon responds.
+    async fn ping(&self) -> Result<(), Error>;
+
+    /// The number of the CPUs of the host the containers run on, if the runtime reports it.
+    async fn host_cpus(&self) -> Option<usize>;
+
+    /// Checks that nothing listens the `port` of the host, so a container can be published on it.
+    async fn is_port_free(&self, port: u16) -> bool;
+
+    async fn container_state(&self, name: &str) -> ContainerState;
+
+    /// The labels the container was created with. It's empty if the container doesn't exist.
+    async fn container_labels(&self, name: &str) -> HashMap<String, String>;
+
+    /// The port of the host the `port` of the container is published on.
+    async fn host_port(&self, name: &str, port: u16) -> Option<u16>;
+
+    /// The exit code of the container that is not running.
+    async fn exit_code(&self, name: &str) -> Option<i64>;
+
+    async fn health(&self, name: &str) -> HealthState;
+
+    async fn create_container(&self, name: &str, spec: ContainerSpec) -> Result<(), Error>;
+
+    async fn start_container(&self, name: &str) -> Result<(), Error>;
+
+    /// Sends `SIGTERM` to the container and doesn't wait for it to exit. The runtime kills the container if it's still
+    /// running after the `timeout`.
+    async fn stop_container(&self, name: &str, timeout: Duration) -> Result<(), Error>;
+
+    async fn kill_container(&self, name: &str) -> Result<(), Error>;
+
+    async fn remove_container(&self, name: &str) -> Result<(), Error>;
+
+    fn logs(&self, name: &str) -> DriverStream<String>;
+
+    fn stats(&self, name: &str) -> DriverStream<StatsData>;
+
+    async fn network_exists(&self, name: &str) -> bool;
+
+    async fn create_network(&self, name: &str) -> Result<(), Error>;
+
+    async fn remove_network(&self, name: &str) -> Result<(), Error>;
+
+    async fn volume_exists(&self, name: &str) -> bool;
+
+    async fn create_volume(&self, name: &str) -> Result<(), Error>;
+
+    async fn remove_volume(&self, name: &str) -> Result<(), Error>;
+}
+
+/// Polls the health of the container with the `interval` and reports the changes only.
+pub(crate) fn poll_health<D: ContainerDriver>(
+    driver: D,
+    name: String,
+    interval: Duration,
+) -> DriverStream<HealthState> {
+    let initial = (driver, name, None);
+    stream::unfold(initial, move |(driver, name, last)| async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            let state = driver.health(&name).await;
+            if last.as_ref() != Some(&state) {
+                return Some((Ok(state.clone()), (driver, name, Some(state))));
+            }
+        }
+    })
+    .boxed()
+}
+
+/// Converts events of a resource into the events of a task. The hub routes only the events of the resource.
+pub(crate) struct EventConv;
+
+impl<E> Converter<ResourceEvent, E> for EventConv
+where
+    E: TryFrom<String> + 'static,
+{
+    fn convert(&mut self, res: Result<ResourceEvent, Error>) -> Option<E> {
+        res.ok()?.action.try_into().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_fields() {
+        let spec = ContainerSpec {
+            image: "registry/app:latest".into(),
+            env: vec!["A=1".into(), "B=2".into()],
+            ..Default::default()
+        };
+        let reordered = ContainerSpec {
+            env: vec!["B=2".into(), "A=1".into()],
+            labels: vec![("key".into(), "value".into())],
+            ..spec.clone()
+        };
+        assert!(spec.changed_fields(&reordered).is_empty());
+        assert_eq!(spec.fingerprint(), reordered.fingerprint());
+
+        let changed = ContainerSpec {
+            image: "registry/app:v2".into(),
+            env: vec!["A=1".into()],
+            ..spec.clone()
+        };
+        assert_eq!(spec.changed_fields(&changed), vec!["image", "env"]);
+    }
+
+    #[test]
+    fn test_fingerprint_stable() {
+        // The value is stored in the labels of the containers, so it must not change between builds
+        let spec = ContainerSpec {
+            image: "registry/app:latest".into(),
+            env: vec!["A=1".into()],
+            ports: vec![PortSpec {
+                container: 8080,
+                host: 8080,
+                exposure: PortExposure::Loopback,
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            spec.fingerprint(),
+            "632b0438123bc1105c9f59a8560fe8e971b1b8fdc8905c735165096ee41ca44f"
+        );
+    }
+}
@@ -30,7 +30,12 @@ use tokio::sync::mpsc;
 use crate::utils::TaskGuard;
 
 pub trait Converter<I, O>: Sync + Send + 'static {
-    fn convert(&self, res: Result<I, Error>) -> Option<O>;
+    fn convert(&mut self, res: Result<I, Error>) -> Option<O>;
+
+    /// The last event when the stream is over.
+    fn finish(&mut self) -> Option<O> {
+        None
+    }
 }
 
 pub struct Forwarder<I, O> {
@@ -66,9 +71,13 @@ where
             if let Some(sdm_event) = self.converter.convert(event) {
                 log::debug!("Sending event: {:?}", sdm_event);
                 if self.sender.send(sdm_event).is_err() {
-                    break;
+                    return;
                 }
             }
         }
+        if let Some(sdm_event) = self.converter.finish() {
+            log::debug!("Sending the final event: {:?}", sdm_event);
+            self.sender.send(sdm_event).ok();
+        }
     }
 }
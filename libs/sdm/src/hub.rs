@@ -0,0 +1,125 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Shares a single stream of the events of the runtime between all the tasks of a scope. The runtime reports only the
+//! events of the resources of the scope.
+//!
+//! The stream is closed when the daemon restarts or the connection is lost. The hub reports the daemon unavailable,
+//! pings it with a backoff and opens the stream again. The events of that period are lost, so the tasks check their
+//! resources again when the daemon is available.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use futures::StreamExt;
+use tari_launchpad_protocol::container::DaemonState;
+use tokio::{
+    sync::{mpsc, watch},
+    time::sleep,
+};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use crate::{
+    driver::{ContainerDriver, DriverStream, ResourceEvent, ResourceKind},
+    utils::{backoff, TaskGuard},
+};
+
+/// The delay before the daemon is checked again when the connection is lost. It's doubled for every next failure.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+type Routes = HashMap<(ResourceKind, String), mpsc::UnboundedSender<ResourceEvent>>;
+
+/// Routes the events of the runtime to the tasks by the kind and the name of the resource.
+#[derive(Clone)]
+pub(crate) struct EventsHub {
+    routes: Arc<Mutex<Routes>>,
+    state: Arc<watch::Sender<DaemonState>>,
+    _guard: TaskGuard<()>,
+}
+
+impl EventsHub {
+    pub fn start<D: ContainerDriver>(scope: &str, driver: D, state: Arc<watch::Sender<DaemonState>>) -> Self {
+        let routes = Arc::new(Mutex::new(Routes::new()));
+        let routine = routine(scope.to_string(), driver, routes.clone(), state.clone());
+        let guard = tokio::spawn(routine).into();
+        Self {
+            routes,
+            state,
+            _guard: guard,
+        }
+    }
+
+    /// The connection to the daemon. It changes to `Available` only after the connection was lost.
+    pub fn daemon_state(&self) -> watch::Receiver<DaemonState> {
+        self.state.subscribe()
+    }
+
+    /// The events of the resource. The previous subscription to the same resource is closed.
+    pub fn subscribe(&self, kind: ResourceKind, name: &str) -> DriverStream<ResourceEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        lock(&self.routes).insert((kind, name.to_string()), tx);
+        UnboundedReceiverStream::new(rx).map(Ok).boxed()
+    }
+}
+
+fn lock(routes: &Mutex<Routes>) -> MutexGuard<'_, Routes> {
+    routes.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+async fn routine<D: ContainerDriver>(
+    scope: String,
+    driver: D,
+    routes: Arc<Mutex<Routes>>,
+    state: Arc<watch::Sender<DaemonState>>,
+) {
+    let mut failures = 0;
+    loop {
+        if failures > 0 {
+            sleep(backoff(failures, RECONNECT_DELAY, MAX_RECONNECT_DELAY)).await;
+        }
+        if let Err(err) = driver.ping().await {
+            failures += 1;
+            log::error!("The daemon is unavailable (attempt {}): {}", failures, err);
+            set_state(&state, DaemonState::DockerUnavailable);
+            continue;
+        }
+        // The stream is opened before the tasks are woken up, so the events of their checks are not lost
+        let mut stream = driver.events(&scope);
+        set_state(&state, DaemonState::Available);
+        while let Some(res) = stream.next().await {
+            match res {
+                Ok(event) => route(&routes, event),
+                Err(err) => {
+                    log::error!("The events stream failed: {}", err);
+                    break;
+                },
+            }
+        }
+        log::warn!("The events stream is closed. Reconnecting...");
+        set_state(&state, DaemonState::DockerUnavailable);
+        failures = 1;
+    }
+}
+
+fn set_state(state: &watch::Sender<DaemonState>, value: DaemonState) {
+    state.send_if_modified(|current| {
+        let changed = *current != value;
+        *current = value;
+        changed
+    });
+}
+
+fn route(routes: &Mutex<Routes>, event: ResourceEvent) {
+    let key = (event.kind, event.name.clone());
+    let mut routes = lock(routes);
+    if let Some(sender) = routes.get(&key) {
+        if sender.send(event).is_err() {
+            // The task doesn't listen anymore
+            routes.remove(&key);
+        }
+    }
+}
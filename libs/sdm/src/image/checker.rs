@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::pin::Pin;
+use std::{future::Future, pin::Pin};
 
 use anyhow::Error;
 use async_trait::async_trait;
@@ -29,28 +29,41 @@ use derive_more::{Deref, DerefMut};
 use futures::stream::{FusedStream, Stream, StreamExt};
 use tari_launchpad_protocol::container::{StatsData, TaskProgress};
 use tokio::{
+    net::TcpStream,
     select,
-    time::{sleep, Duration},
+    time::{sleep, timeout, Duration},
 };
 
 use super::task::Event;
-use crate::{image::ManagedProtocol, scope::ControlEvent, task::TaskSender};
+use crate::{driver::HealthState, image::ManagedProtocol, scope::ControlEvent, task::TaskSender};
+
+/// How long a probe waits for a response.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug)]
 pub enum CheckerEvent {
     Progress(TaskProgress),
     Ready,
+    /// The container doesn't work properly. The dependants are paused until it's healthy again.
+    Unhealthy(String),
+    Healthy,
 }
 
 pub struct CheckerContext<P: ManagedProtocol> {
     logs: Logs,
     stats: Stats,
+    health: Health,
     sender: TaskSender<Event, P>,
 }
 
 impl<P: ManagedProtocol> CheckerContext<P> {
-    pub(crate) fn new(logs: Logs, stats: Stats, sender: TaskSender<Event, P>) -> Self {
-        Self { logs, stats, sender }
+    pub(crate) fn new(logs: Logs, stats: Stats, health: Health, sender: TaskSender<Event, P>) -> Self {
+        Self {
+            logs,
+            stats,
+            health,
+            sender,
+        }
     }
 
     /// Reports the task about the progress.
@@ -67,8 +80,9 @@ impl<P: ManagedProtocol> CheckerContext<P> {
     }
 }
 
-/// Polls a container for the logs and stats, and executes the related hooks for the event. If no events are
-/// received for 1 second, the `on_interval` hook is called. The default implementation of all of the hooks do nothing.
+/// Polls a container for the logs, stats and health, and executes the related hooks for the event. If no events are
+/// received for 1 second, the `on_interval` hook is called. The default implementation of the `on_health_event` hook
+/// reports the `HEALTHCHECK` status of the container, all the other hooks do nothing.
 ///
 /// In each of the hooks, a mutable reference to a `CheckerContext` is provided, which can be used to access / update
 /// the log and stats history, and update the progress of a task.
@@ -91,6 +105,11 @@ pub trait ContainerChecker<P: ManagedProtocol>: Send {
                         ctx.sender.send_stats(msg).ok();
                     }
                 }
+                health_event = ctx.health.next() => {
+                    if let Some(Ok(state)) = health_event {
+                        self.on_health_event(&state, &mut ctx).await;
+                    }
+                }
                 _ = sleep(Duration::from_secs(1)) => {
                     if let Err(err) = self.on_interval(&mut ctx).await {
                         log::error!("On interval checker failed: {}", err);
@@ -104,6 +123,15 @@ pub trait ContainerChecker<P: ManagedProtocol>: Send {
 
     async fn on_stat_event(&mut self, _record: &StatsData, _ctx: &mut CheckerContext<P>) {}
 
+    async fn on_health_event(&mut self, state: &HealthState, ctx: &mut CheckerContext<P>) {
+        let event = match state {
+            HealthState::Healthy => CheckerEvent::Healthy,
+            HealthState::Unhealthy(reason) => CheckerEvent::Unhealthy(reason.clone()),
+            HealthState::NoCheck | HealthState::Starting => return,
+        };
+        ctx.report(event).ok();
+    }
+
     async fn on_interval(&mut self, _ctx: &mut CheckerContext<P>) -> Result<(), Error> {
         Ok(())
     }
@@ -125,6 +153,63 @@ impl<P: ManagedProtocol> ContainerChecker<P> for ReadyIfStarted {
     }
 }
 
+/// Counts the failed probes in a row and reports the container as unhealthy when they reach the `threshold`.
+pub struct Liveness {
+    threshold: u32,
+    failures: u32,
+}
+
+impl Liveness {
+    pub fn new(threshold: u32) -> Self {
+        Self { threshold, failures: 0 }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.failures < self.threshold
+    }
+
+    /// Takes the result of a probe and reports the change of the health.
+    pub fn update<P: ManagedProtocol>(
+        &mut self,
+        probe: Result<(), Error>,
+        ctx: &CheckerContext<P>,
+    ) -> Result<(), Error> {
+        let was_healthy = self.is_healthy();
+        match probe {
+            Ok(()) => {
+                self.failures = 0;
+                if !was_healthy {
+                    ctx.report(CheckerEvent::Healthy)?;
+                }
+            },
+            Err(err) => {
+                self.failures = self.failures.saturating_add(1);
+                if was_healthy && !self.is_healthy() {
+                    let reason = format!("{} probes failed: {}", self.failures, err);
+                    ctx.report(CheckerEvent::Unhealthy(reason))?;
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Checks that a TCP port accepts connections.
+pub async fn tcp_probe(address: &str) -> Result<(), Error> {
+    timeout(PROBE_TIMEOUT, TcpStream::connect(address)).await??;
+    Ok(())
+}
+
+/// Checks that a gRPC server responds to the `request`.
+pub async fn grpc_probe<F, T, E>(request: F) -> Result<T, Error>
+where
+    F: Future<Output = Result<T, E>>,
+    Error: From<E>,
+{
+    let response = timeout(PROBE_TIMEOUT, request).await??;
+    Ok(response)
+}
+
 #[derive(Deref, DerefMut)]
 pub struct Logs {
     stream: Pin<Box<dyn FusedStream<Item = Result<String, Error>> + Send>>,
@@ -158,3 +243,20 @@ impl Stats {
         }
     }
 }
+
+#[derive(Deref, DerefMut)]
+pub struct Health {
+    stream: Pin<Box<dyn FusedStream<Item = Result<HealthState, Error>> + Send>>,
+}
+
+impl Health {
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<HealthState, Error>>,
+        S: Send + 'static,
+    {
+        Self {
+            stream: Box::pin(stream.fuse()),
+        }
+    }
+}
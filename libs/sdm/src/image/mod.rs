@@ -24,13 +24,19 @@
 pub mod checker;
 mod task;
 
-use std::fmt;
+use std::{fmt, time::Duration};
 
 use checker::{ContainerChecker, ReadyIfStarted};
 use tari_launchpad_protocol::container::TaskId;
 pub(crate) use task::ImageTask;
 
-use crate::config::ManagedProtocol;
+use crate::{
+    config::ManagedProtocol,
+    driver::{PortExposure, PortSpec, ResourceLimits},
+};
+
+/// The length of a CPU period the quota is measured in, the default of the runtime.
+const CPU_PERIOD: i64 = 100_000;
 
 /// A container that can be managed by SDM.
 pub trait ManagedContainer: fmt::Debug + Send + 'static {
@@ -48,6 +54,12 @@ pub trait ManagedContainer: fmt::Debug + Send + 'static {
 
     fn on_event(&mut self, _event: <Self::Protocol as ManagedProtocol>::Inner) {}
 
+    /// Reports once that the last `reconfigure` changed the settings the running container was created with, so the
+    /// container has to be recreated.
+    fn restart_required(&mut self) -> bool {
+        false
+    }
+
     fn registry(&self) -> &str;
 
     fn image_name(&self) -> &str;
@@ -56,6 +68,16 @@ pub trait ManagedContainer: fmt::Debug + Send + 'static {
         "latest"
     }
 
+    /// How long the container has to exit after `SIGTERM` before it's killed.
+    fn stop_timeout(&self) -> Duration {
+        Duration::from_secs(10)
+    }
+
+    /// What to do when the container exits by itself.
+    fn restart_policy(&self) -> RestartPolicy {
+        RestartPolicy::OnFailure { max_retries: 5 }
+    }
+
     fn args(&self, _args: &mut Args) {}
 
     fn envs(&self, _envs: &mut Envs) {}
@@ -67,6 +89,34 @@ pub trait ManagedContainer: fmt::Debug + Send + 'static {
     fn volumes(&self, _volumes: &mut Volumes) {}
 
     fn mounts(&self, _mounts: &mut Mounts) {}
+
+    fn limits(&self, _limits: &mut Limits) {}
+}
+
+/// The full reference of the image that depends on the config.
+pub fn fqdn<C: ManagedProtocol>(image: &dyn ManagedContainer<Protocol = C>) -> String {
+    format!("{}/{}:{}", image.registry(), image.image_name(), image.tag())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    Never,
+    /// Restarts the container if it exited with a non-zero code, but not more than `max_retries` times in a row.
+    OnFailure {
+        max_retries: u32,
+    },
+    Always,
+}
+
+impl RestartPolicy {
+    /// Checks whether the container should be started again after the `attempt` restarts in a row.
+    pub fn should_restart(&self, exit_code: Option<i64>, attempt: u32) -> bool {
+        match self {
+            Self::Never => false,
+            Self::OnFailure { max_retries } => exit_code != Some(0) && attempt < *max_retries,
+            Self::Always => true,
+        }
+    }
 }
 
 #[derive(Default)]
@@ -107,20 +157,30 @@ impl Envs {
 }
 
 #[derive(Default)]
-pub struct Ports(Vec<u16>);
+pub struct Ports(Vec<PortSpec>);
 
 impl Ports {
     pub fn add(&mut self, port: u16) {
-        // let item = (port, port);
-        self.0.push(port);
+        self.forward(port, port);
     }
 
-    // pub fn forward(&mut self, from: u16, to: u16) {
-    // let item = (from, to);
-    // self.0.push(item);
-    // }
+    /// Publishes the port `to` of the container on the port `from` of the host. The port is reachable from the host
+    /// only.
+    pub fn forward(&mut self, from: u16, to: u16) {
+        self.publish(from, to, PortExposure::default());
+    }
+
+    /// Publishes the port `to` of the container on the port `from` of the host with the `exposure`.
+    pub fn publish(&mut self, from: u16, to: u16, exposure: PortExposure) {
+        let item = PortSpec {
+            container: to,
+            host: from,
+            exposure,
+        };
+        self.0.push(item);
+    }
 
-    pub fn build(self) -> Vec<u16> {
+    pub fn build(self) -> Vec<PortSpec> {
         self.0
     }
 }
@@ -180,3 +240,46 @@ impl Mounts {
         self.0
     }
 }
+
+/// The limits of the resources of a container. Everything is unlimited by default.
+#[derive(Default)]
+pub struct Limits(ResourceLimits);
+
+impl Limits {
+    /// Limits the CPU time to the number of the `cpus`, e.g. `1.5`.
+    pub fn cpus(&mut self, cpus: f64) {
+        self.0.cpu_period = Some(CPU_PERIOD);
+        self.0.cpu_quota = Some((cpus * CPU_PERIOD as f64).round() as i64);
+    }
+
+    /// Sets the weight of the container for the CPU time. The default weight is `1024`.
+    pub fn cpu_shares(&mut self, shares: u64) {
+        self.0.cpu_shares = Some(to_i64(shares));
+    }
+
+    /// Allows the container to run on the `cpus` only, e.g. `0-3` or `0,1`.
+    pub fn cpuset(&mut self, cpus: impl ToString) {
+        self.0.cpuset_cpus = Some(cpus.to_string());
+    }
+
+    pub fn memory(&mut self, bytes: u64) {
+        self.0.memory = Some(to_i64(bytes));
+    }
+
+    /// Limits the memory and the swap together, so it has to be not less than the memory.
+    pub fn memory_swap(&mut self, bytes: u64) {
+        self.0.memory_swap = Some(to_i64(bytes));
+    }
+
+    pub fn pids(&mut self, max: u64) {
+        self.0.pids_limit = Some(to_i64(max));
+    }
+
+    pub fn build(self) -> ResourceLimits {
+        self.0
+    }
+}
+
+fn to_i64(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
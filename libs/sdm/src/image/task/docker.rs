@@ -21,129 +21,188 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::{collections::HashMap, path::Path};
+use std::{sync::Arc, time::Duration};
 
 use anyhow::{anyhow, Error};
-use bollard::{
-    container::{
-        Config, CreateContainerOptions, LogOutput, LogsOptions, NetworkingConfig, RemoveContainerOptions,
-        Stats as BollardStats, StatsOptions,
-    },
-    errors::Error as BollardError,
-    image::{CreateImageOptions, RemoveImageOptions},
-    models::{
-        ContainerInspectResponse, CreateImageInfo, EndpointSettings, EventMessage, EventMessageTypeEnum, HostConfig,
-        Mount as BollardMount, MountTypeEnum, PortBinding, PortMap,
-    },
-    system::EventsOptions,
-};
-use chrono::Local;
-use futures::{StreamExt, TryStreamExt};
-use tari_launchpad_protocol::container::{StatsData, TaskProgress};
+use futures::StreamExt;
+use tari_launchpad_protocol::container::TaskProgress;
+use tokio::sync::Semaphore;
 
-use super::{ContainerState, Event, ImageTask};
+use super::{progress::PullTracker, Event, ImageTask};
 use crate::{
     config::ManagedProtocol,
+    driver::{
+        poll_health, ContainerDriver, ContainerSpec, ContainerState, EventConv, MountSpec, PortSpec, PullProgress,
+        ResourceKind, SCOPE_LABEL,
+    },
     forwarder::{Converter, Forwarder},
     image::{
-        checker::{Logs, Stats},
-        Args, Envs, Mount, Mounts, Networks, Ports, Volumes,
+        checker::{Health, Logs, Stats},
+        Args, Envs, Limits, Mount, Mounts, Networks, Ports, Volumes,
     },
+    registry::UpdateChecker,
     task::TaskContext,
     utils::TaskGuard,
 };
 
-// TODO: Methods could be moved to `TaskContext` or `ScopedDockerDriver`
+/// How often the health of a running container is checked.
+const HEALTH_INTERVAL: Duration = Duration::from_secs(2);
+/// The labels that identify the containers of the tasks.
+const TASK_LABEL: &str = "com.tari.launchpad.task";
+const SPEC_LABEL: &str = "com.tari.launchpad.spec";
+
 // Container management
-impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
+impl<C: ManagedProtocol, D: ContainerDriver> TaskContext<ImageTask<C>, D> {
     pub fn subscribe_to_events(&mut self) {
-        let mut type_filter = HashMap::new();
-        type_filter.insert("type".to_string(), vec!["container".to_string()]);
-        type_filter.insert("container".to_string(), vec![self.inner.container_name.clone()]);
-        let opts = EventsOptions {
-            since: None,
-            until: None,
-            filters: type_filter,
-        };
-        let stream = self.driver.events(Some(opts)).map_err(Error::from);
+        let stream = self.resource_events(ResourceKind::Container, &self.inner.container_name);
         let sender = self.sender().get_direct().clone();
-        let conv = EventConv {
-            // TODO: Name is not necessary here
-            name: self.inner.container_name.clone(),
-        };
-        let handler = Forwarder::start(stream, conv, sender);
+        let handler = Forwarder::start(stream, EventConv, sender);
         self.inner.events = Some(handler);
     }
 
     pub async fn image_exists(&mut self) -> bool {
-        self.driver.inspect_image(&self.inner.image_name).await.is_ok()
+        self.driver.image_exists(&self.inner.image_name).await
     }
 
     pub async fn container_state(&mut self) -> ContainerState {
-        let res = self.driver.inspect_container(&self.inner.container_name, None).await;
-        // log::trace!("State of container {}: {:?}", self.inner.container_name, res);
-        match res {
-            Ok(ContainerInspectResponse { state: Some(state), .. }) => {
-                if state.running.unwrap_or_default() {
-                    ContainerState::Running
-                } else {
-                    ContainerState::NotRunning
-                }
-            },
-            Ok(_) => ContainerState::NotRunning,
-            Err(_) => ContainerState::NotFound,
-        }
+        self.driver.container_state(&self.inner.container_name).await
     }
 
     pub fn pull(&mut self) -> TaskGuard<()> {
-        let opts = Some(CreateImageOptions {
-            from_image: self.inner.image_name.clone(),
-            ..Default::default()
-        });
-        let stream = self.driver.create_image(opts, None, None).map_err(Error::from);
+        let stream = self.driver.pull_image(&self.inner.image_name);
         let sender = self.sender().get_direct().clone();
-        Forwarder::start(stream, ProgressConv, sender)
+        Forwarder::start(stream, ProgressConv::default(), sender)
     }
 
-    pub fn logs_stream(&mut self) -> Logs {
-        let opts = LogsOptions::<String> {
-            follow: true,
-            stdout: true,
-            stderr: true,
-            ..Default::default()
+    /// Compares the image with the registry in the background and reports the result as an event.
+    pub fn spawn_update_check(&mut self, checker: UpdateChecker) -> TaskGuard<()> {
+        let driver = self.driver.clone();
+        let image = self.inner.image_name.clone();
+        let sender = self.sender().get_direct().clone();
+        let fut = async move {
+            match checker.is_outdated(&driver, &image).await {
+                Ok(available) => {
+                    sender.send(Event::UpdateChecked { image, available }).ok();
+                },
+                Err(err) => {
+                    log::warn!("Can't check the updates of the image {}: {}", image, err);
+                },
+            }
+        };
+        tokio::spawn(fut).into()
+    }
+
+    /// Pulls the image in the background when a permit is acquired. The state of the task isn't changed.
+    pub fn spawn_prefetch(&mut self, permits: Arc<Semaphore>) -> TaskGuard<()> {
+        let driver = self.driver.clone();
+        let image = self.inner.image_name.clone();
+        let sender = self.sender().get_direct().clone();
+        let fut = async move {
+            sender
+                .send(Event::PrefetchProgress(TaskProgress::new("Waiting...")))
+                .ok();
+            // The semaphore is never closed
+            let Ok(_permit) = permits.acquire_owned().await else {
+                return;
+            };
+            let mut tracker = PullTracker::default();
+            let mut stream = driver.pull_image(&image);
+            while let Some(res) = stream.next().await {
+                match res {
+                    Ok(record) => {
+                        if let Some(progress) = tracker.update(record) {
+                            sender.send(Event::PrefetchProgress(progress)).ok();
+                        }
+                    },
+                    Err(err) => {
+                        sender.send(Event::Prefetched(Err(err.to_string()))).ok();
+                        return;
+                    },
+                }
+            }
+            sender.send(Event::Prefetched(Ok(()))).ok();
         };
-        let stream = self
-            .driver
-            .logs(&self.inner.container_name, Some(opts))
-            .map(log_conv)
-            .map_err(Error::from);
+        tokio::spawn(fut).into()
+    }
+
+    pub fn logs_stream(&mut self) -> Logs {
+        let stream = self.driver.logs(&self.inner.container_name);
         Logs::new(stream)
     }
 
     pub fn stats_stream(&mut self) -> Stats {
-        let opts = StatsOptions {
-            stream: true,
-            one_shot: false,
-        };
-        let stream = self
-            .driver
-            .stats(&self.inner.container_name, Some(opts))
-            .map(stat_conv)
-            .map_err(Error::from);
+        let stream = self.driver.stats(&self.inner.container_name);
         Stats::new(stream)
     }
 
+    pub fn health_stream(&mut self) -> Health {
+        let stream = poll_health(self.driver.clone(), self.inner.container_name.clone(), HEALTH_INTERVAL);
+        Health::new(stream)
+    }
+
     pub async fn try_create_container(&mut self) -> Result<(), Error> {
+        let mut spec = self.container_spec()?;
+        spec.labels = self.labels(&spec);
+        self.inner.spec = Some(spec.clone());
+        self.driver.create_container(&self.inner.container_name, spec).await
+    }
+
+    /// Restarts the existing container if the config changed its spec.
+    pub fn check_spec(&mut self) {
+        let Some(current) = self.inner.spec.as_ref() else {
+            return;
+        };
+        // The spec can't be built without the dependencies, they are checked by the state machine
+        let Ok(spec) = self.container_spec() else {
+            return;
+        };
+        let fields = current.changed_fields(&spec);
+        if fields.is_empty() {
+            return;
+        }
+        log::info!(
+            "The spec of the container {} changed: {}",
+            self.inner.container_name,
+            fields.join(", ")
+        );
+        // The new spec is taken when the container is created again
+        self.inner.spec = None;
+        self.inner.force_restart = true;
+        if let Err(err) = self.sender().send_spec_changed(&fields) {
+            log::error!(
+                "Can't report the changed spec of {}: {}",
+                self.inner.container_name,
+                err
+            );
+        }
+    }
+
+    /// Checks that the container was created by the task with the same spec.
+    pub async fn is_adoptable(&mut self) -> bool {
+        let Ok(spec) = self.container_spec() else {
+            return false;
+        };
+        let labels = self.driver.container_labels(&self.inner.container_name).await;
+        self.labels(&spec)
+            .iter()
+            .all(|(key, value)| labels.get(key) == Some(value))
+    }
+
+    fn labels(&self, spec: &ContainerSpec) -> Vec<(String, String)> {
+        vec![
+            (SCOPE_LABEL.to_string(), self.inner.scope.clone()),
+            (TASK_LABEL.to_string(), self.sender().task_id().to_string()),
+            (SPEC_LABEL.to_string(), spec.fingerprint()),
+        ]
+    }
+
+    pub fn container_spec(&self) -> Result<ContainerSpec, Error> {
         let mut args = Args::default();
         self.inner.image.args(&mut args);
         let mut ports = Ports::default();
         self.inner.image.ports(&mut ports);
         let mut envs = Envs::default();
         self.inner.image.envs(&mut envs);
-        let opts = CreateContainerOptions {
-            name: self.inner.container_name.clone(),
-            platform: None,
-        };
 
         let mut networks = Networks::default();
         self.inner.image.networks(&mut networks);
@@ -151,122 +210,87 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
 
         let mut volumes = Volumes::default();
         self.inner.image.volumes(&mut volumes);
-        let volumes = volumes_map(volumes.build());
 
         let mut mounts = Mounts::default();
         self.inner.image.mounts(&mut mounts);
         let mounts = self.mounts_map(mounts.build())?;
-        let ports = ports.build();
-        let config = Config {
-            image: Some(self.inner.image_name.clone()),
-            attach_stdin: Some(false),
-            attach_stdout: Some(false),
-            attach_stderr: Some(false),
-            exposed_ports: Some(exposed_ports(&ports)),
-            open_stdin: Some(true),
-            stdin_once: Some(false),
-            tty: Some(true),
-            env: Some(envs.build()),
-            volumes: Some(volumes),
-            cmd: Some(args.build()),
-            host_config: Some(HostConfig {
-                binds: Some(vec![]),
-                network_mode: Some("bridge".to_string()),
-                port_bindings: Some(ports_map(&ports)),
-                mounts: Some(mounts),
-                ..Default::default()
-            }),
-            networking_config: Some(networks),
-            ..Default::default()
+
+        let mut limits = Limits::default();
+        self.inner.image.limits(&mut limits);
+
+        let spec = ContainerSpec {
+            image: self.inner.image_name.clone(),
+            cmd: args.build(),
+            env: envs.build(),
+            ports: ports.build(),
+            volumes: volumes.build(),
+            networks,
+            mounts,
+            limits: limits.build(),
+            labels: Vec::new(),
         };
-        self.driver.create_container(Some(opts), config).await?;
-        Ok(())
+        Ok(spec)
+    }
+
+    /// Finds a port of the host the container has to be published on, but that is taken already.
+    pub async fn busy_port(&mut self) -> Option<u16> {
+        let mut ports = Ports::default();
+        self.inner.image.ports(&mut ports);
+        for port in ports.build().into_iter().filter(PortSpec::is_published) {
+            if !self.driver.is_port_free(port.host).await {
+                return Some(port.host);
+            }
+        }
+        None
     }
 
     pub async fn try_start_container(&mut self) -> Result<(), Error> {
-        self.driver
-            .start_container::<String>(&self.inner.container_name, None)
-            .await?;
-        Ok(())
+        self.driver.start_container(&self.inner.container_name).await
+    }
+
+    pub async fn try_stop_container(&mut self) -> Result<(), Error> {
+        let timeout = self.inner.image.stop_timeout();
+        self.driver.stop_container(&self.inner.container_name, timeout).await
     }
 
     pub async fn try_kill_container(&mut self) -> Result<(), Error> {
-        self.driver
-            .kill_container::<String>(&self.inner.container_name, None)
-            .await?;
-        Ok(())
+        self.driver.kill_container(&self.inner.container_name).await
     }
 
     pub async fn try_remove_container(&mut self) -> Result<(), Error> {
-        let opts = RemoveContainerOptions {
-            force: true,
-            ..Default::default()
-        };
-        self.driver
-            .remove_container(&self.inner.container_name, Some(opts))
-            .await?;
-        Ok(())
+        self.driver.remove_container(&self.inner.container_name).await
     }
 
     pub async fn try_remove_image(&mut self) -> Result<(), Error> {
-        let image_name = self.inner.image_name.as_ref();
-        let opts = Some(RemoveImageOptions {
-            force: true,
-            ..Default::default()
-        });
-        self.driver.remove_image(image_name, opts, None).await?;
-        Ok(())
+        self.driver.remove_image(&self.inner.image_name).await
     }
 
-    fn networks_map(&self, networks: Networks) -> Result<NetworkingConfig<String>, Error> {
-        let mut endpoints = HashMap::new();
+    fn networks_map(&self, networks: Networks) -> Result<Vec<(String, String)>, Error> {
+        let mut result = Vec::new();
         for (alias, resource) in networks.build() {
             let net_name = self
                 .resource(&resource)
                 .ok_or_else(|| anyhow!("Network {:?} not available in resources. Check dependencies.", resource))?
                 .to_string();
-            let endpoint = EndpointSettings {
-                aliases: Some(vec![alias]),
-                ..Default::default()
-            };
-            endpoints.insert(net_name, endpoint);
+            result.push((net_name, alias));
         }
-        Ok(NetworkingConfig {
-            endpoints_config: endpoints,
-        })
+        Ok(result)
     }
 
-    fn make_mount(&self, mount: Mount) -> Result<BollardMount, Error> {
+    fn make_mount(&self, mount: Mount) -> Result<MountSpec, Error> {
         match mount {
             Mount::ToVolume { volume, target } => {
-                let resource = self
+                let source = self
                     .resource(&volume)
                     .ok_or_else(|| anyhow!("Resource {:?} was not found.", volume))?
                     .to_string();
-                let mount = BollardMount {
-                    target: Some(target),
-                    source: Some(resource),
-                    typ: Some(MountTypeEnum::VOLUME),
-                    volume_options: None,
-                    ..Default::default()
-                };
-                Ok(mount)
-            },
-            Mount::BindTo { source, target } => {
-                let source = canonicalize(source);
-                let mount = BollardMount {
-                    target: Some(target),
-                    source: Some(source),
-                    typ: Some(MountTypeEnum::BIND),
-                    bind_options: None,
-                    ..Default::default()
-                };
-                Ok(mount)
+                Ok(MountSpec::Volume { source, target })
             },
+            Mount::BindTo { source, target } => Ok(MountSpec::Bind { source, target }),
         }
     }
 
-    fn mounts_map(&self, mounts: Vec<Mount>) -> Result<Vec<BollardMount>, Error> {
+    fn mounts_map(&self, mounts: Vec<Mount>) -> Result<Vec<MountSpec>, Error> {
         let mut result = Vec::new();
         for mount in mounts {
             result.push(self.make_mount(mount)?);
@@ -275,128 +299,26 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
     }
 }
 
-type BollardMap = HashMap<String, HashMap<(), ()>>;
-
-fn volumes_map(volumes: Vec<String>) -> BollardMap {
-    let mut result = BollardMap::new();
-    for volume in volumes {
-        result.insert(volume, HashMap::default());
-    }
-    result
-}
-
-fn exposed_ports(ports: &[u16]) -> BollardMap {
-    let mut result = BollardMap::new();
-    for port in ports {
-        result.insert(format!("{}/tcp", port), HashMap::default());
-    }
-    result
-}
-
-fn ports_map(ports: &[u16]) -> PortMap {
-    let mut result = PortMap::new();
-    for (k, _) in exposed_ports(ports) {
-        let binding = vec![PortBinding {
-            host_ip: Some(String::new()),
-            host_port: Some(k.clone()),
-        }];
-        result.insert(k, Some(binding));
-    }
-    result
+/// Collects the progress of the layers of the image.
+#[derive(Default)]
+struct ProgressConv {
+    tracker: PullTracker,
+    failed: bool,
 }
 
-fn log_conv(res: Result<LogOutput, BollardError>) -> Result<String, Error> {
-    if let Ok(LogOutput::Console { message }) = res {
-        match std::str::from_utf8(message.as_ref()) {
-            Ok(data) => {
-                let message = data.to_string();
-                log::trace!("Log: {}", message);
-                Ok(message)
+impl Converter<PullProgress, Event> for ProgressConv {
+    fn convert(&mut self, res: Result<PullProgress, Error>) -> Option<Event> {
+        match res {
+            Ok(info) => self.tracker.update(info).map(Event::PullingProgress),
+            Err(err) => {
+                log::error!("Error while pulling image: {}", err);
+                self.failed = true;
+                Some(Event::PullingFailed(err.to_string()))
             },
-            Err(err) => Err(anyhow!("Can't parse log text: {}", err)),
         }
-    } else {
-        Err(anyhow!("Unsupported log event: {:?}", res))
     }
-}
 
-fn stat_conv(res: Result<BollardStats, BollardError>) -> Result<StatsData, Error> {
-    if let Ok(BollardStats {
-        cpu_stats,
-        memory_stats,
-        ..
-    }) = res
-    {
-        Ok(StatsData {
-            timestamp: Local::now().naive_local(),
-            system_cpu_usage: cpu_stats.system_cpu_usage.unwrap_or_default() as i64,
-            cpu_usage: cpu_stats.cpu_usage.total_usage as i64,
-            mem_limit: memory_stats.limit.unwrap_or_default().into(),
-            mem_usage: memory_stats.usage.unwrap_or_default().into(),
-        })
-    } else {
-        Err(anyhow!("Unsupported stats event: {:?}", res))
+    fn finish(&mut self) -> Option<Event> {
+        (!self.failed).then_some(Event::Pulled)
     }
 }
-
-struct ProgressConv;
-
-impl Converter<CreateImageInfo, Event> for ProgressConv {
-    fn convert(&self, res: Result<CreateImageInfo, Error>) -> Option<Event> {
-        if let Err(err) = res {
-            log::error!("Error while pulling image: {}", err);
-            return Some(Event::PullingFailed(err.to_string()));
-        }
-        let info = res.unwrap();
-        log::debug!("Created Image Info: {:?}", info);
-        let details = info.progress_detail?;
-        let current = details.current? * 100;
-        let total = details.total?;
-        let pct = current / total;
-        let stage = info.status?;
-        let progress = TaskProgress { pct: pct as u8, stage };
-        Some(Event::PullingProgress(progress))
-    }
-}
-
-struct EventConv {
-    pub name: String,
-}
-
-impl Converter<EventMessage, Event> for EventConv {
-    fn convert(&self, res: Result<EventMessage, Error>) -> Option<Event> {
-        if let Ok(EventMessage {
-            typ: Some(typ),
-            action: Some(action),
-            actor: Some(actor),
-            ..
-        }) = res
-        {
-            if let Some(attributes) = actor.attributes {
-                if let Some(name) = attributes.get("name") {
-                    // TODO: Check images as well
-                    if self.name == *name {
-                        // TODO: Check the name
-                        if let EventMessageTypeEnum::CONTAINER = typ {
-                            return action.try_into().ok();
-                        }
-                    } else {
-                        log::error!("Message for other container {}, but expected {}", name, self.name);
-                    }
-                }
-            }
-        }
-        None
-    }
-}
-
-// FIXME: This might be replaceable by std::fs::canonicalize, but I don't have a windows machine to check
-fn canonicalize<P: AsRef<Path>>(path: P) -> String {
-    #[cfg(target_os = "windows")]
-    let path = path.as_ref().to_string_lossy().to_string();
-    #[cfg(target_os = "macos")]
-    let path = format!("/host_mnt{}", path.as_ref().to_string_lossy());
-    #[cfg(target_os = "linux")]
-    let path = path.as_ref().to_string_lossy().to_string();
-    path
-}
@@ -23,26 +23,33 @@
 
 use anyhow::Error;
 use tari_launchpad_protocol::container::{TaskProgress, TaskStatus};
+use tokio::time::Instant;
 
 use super::{Event, ImageTask, Status};
 use crate::{
     config::ManagedProtocol,
+    driver::ContainerDriver,
     image::checker::{CheckerContext, CheckerEvent},
     task::TaskContext,
+    utils::TaskGuard,
 };
 
-impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
+impl<C: ManagedProtocol, D: ContainerDriver> TaskContext<ImageTask<C>, D> {
     pub fn process_event_impl(&mut self, event: Event) -> Result<(), Error> {
         log::trace!("Image event triggered. Image: {} Event: {event:?}", self.image_name);
         match event {
             Event::Created => self.on_created(),
             Event::PullingProgress(value) => self.on_pulling_progress(value),
             Event::PullingFailed(reason) => self.on_pulling_failed(reason),
+            Event::Pulled => self.on_pulled(),
             Event::Destroyed => self.on_destroyed(),
             Event::Started => self.on_started(),
             Event::Killed => self.on_killed(),
             Event::Terminated => self.on_terminated(),
             Event::CheckerProgress(event) => self.on_checker_event(event),
+            Event::UpdateChecked { image, available } => self.on_update_checked(image, available),
+            Event::PrefetchProgress(progress) => self.sender().send_pull_progress(progress),
+            Event::Prefetched(result) => self.on_prefetched(result),
         }
     }
 
@@ -68,8 +75,14 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
         Ok(())
     }
 
+    fn on_pulled(&mut self) -> Result<(), Error> {
+        // The update that follows the event checks the image
+        log::debug!("The pull of the image {} is finished", self.image_name);
+        Ok(())
+    }
+
     fn on_destroyed(&mut self) -> Result<(), Error> {
-        if let Status::WaitContainerRemoved = self.status.get() {
+        if let Status::WaitContainerRemoved { .. } = self.status.get() {
             self.status.set(Status::CleanDangling);
         }
         Ok(())
@@ -77,27 +90,55 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
 
     fn on_started(&mut self) -> Result<(), Error> {
         if let Status::WaitContainerStarted { .. } = self.status.get() {
-            let checker = self.inner.image.checker();
-            let logs = self.logs_stream();
-            let stats = self.stats_stream();
-            let sender = self.sender().clone();
-            let context = CheckerContext::new(logs, stats, sender);
-            let fur = checker.entrypoint(context);
-            let checker = tokio::spawn(fur).into();
-            self.status.set(Status::Active { checker, ready: false });
+            self.activate();
         }
         Ok(())
     }
 
+    /// Subscribes to the logs and the stats of the running container and spawns its checker.
+    pub(super) fn activate(&mut self) {
+        let checker = self.spawn_checker();
+        self.inner.started_at = Some(Instant::now());
+        self.status.set(Status::Active {
+            checker,
+            ready: false,
+            healthy: true,
+        });
+    }
+
+    /// Spawns the checker again with the new streams. The readiness of the container is kept.
+    pub(super) fn resubscribe(&mut self) {
+        let checker = self.spawn_checker();
+        self.status.update(|status| {
+            if let Status::Active { checker: current, .. } = status {
+                *current = checker;
+            }
+        });
+    }
+
+    fn spawn_checker(&mut self) -> TaskGuard<()> {
+        let checker = self.inner.image.checker();
+        let logs = self.logs_stream();
+        let stats = self.stats_stream();
+        let health = self.health_stream();
+        let sender = self.sender().clone();
+        let context = CheckerContext::new(logs, stats, health, sender);
+        let fur = checker.entrypoint(context);
+        tokio::spawn(fur).into()
+    }
+
     fn on_killed(&mut self) -> Result<(), Error> {
         Ok(())
     }
 
     fn on_checker_event(&mut self, event: CheckerEvent) -> Result<(), Error> {
-        if let Status::Active { .. } = self.status.get() {
+        if let Status::Active { ready, healthy, .. } = *self.status.get() {
             match event {
                 CheckerEvent::Progress(progress) => {
-                    self.update_task_status(TaskStatus::Progress(progress))?;
+                    // Keeps the reason of the unhealthy state visible
+                    if healthy {
+                        self.update_task_status(TaskStatus::Progress(progress))?;
+                    }
                 },
                 CheckerEvent::Ready => {
                     self.status.update(|status| {
@@ -105,21 +146,80 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
                             *ready = true;
                         }
                     });
-                    self.update_task_status(TaskStatus::Active)?;
+                    if healthy {
+                        self.update_task_status(TaskStatus::Active)?;
+                    }
+                },
+                CheckerEvent::Unhealthy(reason) => {
+                    log::warn!("Container {} is unhealthy: {}", self.inner.container_name, reason);
+                    self.set_healthy(false);
+                    self.update_task_status(TaskStatus::Unhealthy(reason))?;
+                },
+                CheckerEvent::Healthy => {
+                    if !healthy {
+                        log::info!("Container {} is healthy again", self.inner.container_name);
+                        self.set_healthy(true);
+                        let status = if ready {
+                            TaskStatus::Active
+                        } else {
+                            TaskStatus::Progress(TaskProgress::new("Starting..."))
+                        };
+                        self.update_task_status(status)?;
+                    }
                 },
             }
         }
         Ok(())
     }
 
+    fn on_update_checked(&mut self, image: String, available: bool) -> Result<(), Error> {
+        self.inner.updates.running = None;
+        // The result is outdated if the image was changed by the config
+        if image == self.inner.image_name && available != self.inner.updates.available {
+            if available {
+                log::info!("A new version of the image {} is available", image);
+            }
+            self.inner.updates.available = available;
+            self.sender().send_update_available(available)?;
+        }
+        Ok(())
+    }
+
+    fn on_prefetched(&mut self, result: Result<(), String>) -> Result<(), Error> {
+        self.inner.prefetch = None;
+        let progress = match result {
+            Ok(()) => {
+                log::info!("Image {} is pulled", self.inner.image_name);
+                TaskProgress {
+                    pct: 100,
+                    ..TaskProgress::new("Pulled")
+                }
+            },
+            Err(reason) => {
+                log::error!("Can't pull the image {}: {}", self.inner.image_name, reason);
+                self.sender().send_error(reason)?;
+                TaskProgress::new("Failed")
+            },
+        };
+        self.sender().send_pull_progress(progress)
+    }
+
+    fn set_healthy(&mut self, value: bool) {
+        self.status.update(|status| {
+            if let Status::Active { healthy, .. } = status {
+                *healthy = value;
+            }
+        });
+    }
+
     fn on_terminated(&mut self) -> Result<(), Error> {
         match self.status.get() {
-            Status::WaitContainerKilled => {
+            Status::WaitContainerStopped { .. } | Status::WaitContainerKilled { .. } => {
                 self.status.set(Status::CleanDangling);
             },
             Status::Active { .. } => {
-                // TODO: Add waiting interval + fallback
-                // self.status.set(Status::CleanDangling);
+                log::warn!("Container {} exited unexpectedly", self.inner.container_name);
+                self.status.set(Status::ContainerDied);
             },
             _ => {},
         }
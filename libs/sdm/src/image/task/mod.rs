@@ -23,22 +23,32 @@
 
 mod docker;
 mod events;
+mod progress;
 mod update;
 
+use std::sync::Arc;
+
 use anyhow::Error;
 use async_trait::async_trait;
 use tari_launchpad_protocol::container::TaskProgress;
+use tokio::{
+    sync::Semaphore,
+    time::{Duration, Instant},
+};
 
-use super::{checker::CheckerEvent, ManagedContainer};
+use super::{checker::CheckerEvent, fqdn, ManagedContainer};
 use crate::{
     config::ManagedProtocol,
+    driver::{ContainerDriver, ContainerSpec},
     error::ParseError,
+    registry::UpdateChecker,
     task::{RunnableContext, RunnableTask, TaskContext, TaskEvent, TaskStatusChecker},
     utils::TaskGuard,
 };
 
 pub struct ImageTask<C: ManagedProtocol> {
     events: Option<TaskGuard<()>>,
+    scope: String,
     container_name: String,
     // TODO: Rename to `fqdn`
     image_name: String,
@@ -47,20 +57,57 @@ pub struct ImageTask<C: ManagedProtocol> {
     force_restart: bool,
     /// A flag to drop and pull image again
     force_pull: bool,
+    /// The registry or the tag was changed by the config
+    image_changed: bool,
+    /// The number of the restarts in a row after the container exited by itself
+    restarts: u32,
+    started_at: Option<Instant>,
+    updates: UpdateState,
+    /// Pulls the image ahead of time
+    prefetch: Option<TaskGuard<()>>,
+    /// The running container of the previous run can be adopted. It's checked once on start.
+    adopt: bool,
+    /// The spec the existing container was created with, or the spec of the last attempt to create it.
+    spec: Option<ContainerSpec>,
+    /// The running container is checked again, because the daemon was unavailable.
+    resync: bool,
+}
+
+/// The checks of the newer versions of the image.
+struct UpdateState {
+    /// Compares the local image with the registry if it's set
+    checker: Option<UpdateChecker>,
+    next_check: Instant,
+    running: Option<TaskGuard<()>>,
+    available: bool,
 }
 
 impl<C: ManagedProtocol> ImageTask<C> {
-    pub fn new(scope: &str, image: Box<dyn ManagedContainer<Protocol = C>>) -> Self {
+    pub fn new(scope: &str, image: Box<dyn ManagedContainer<Protocol = C>>, updates: Option<UpdateChecker>) -> Self {
         // let required = image.deps().into_iter().collect();
-        let image_name = format!("{}/{}:{}", image.registry(), image.image_name(), image.tag());
+        let image_name = fqdn(image.as_ref());
         let container_name = format!("{}_{}", scope, image.image_name());
         Self {
             events: None,
+            scope: scope.to_string(),
             container_name,
             image_name,
             image,
             force_restart: false,
             force_pull: false,
+            image_changed: false,
+            restarts: 0,
+            started_at: None,
+            updates: UpdateState {
+                checker: updates,
+                next_check: Instant::now(),
+                running: None,
+                available: false,
+            },
+            prefetch: None,
+            adopt: true,
+            spec: None,
+            resync: false,
         }
     }
 }
@@ -78,22 +125,59 @@ impl<C: ManagedProtocol> RunnableTask for ImageTask<C> {
     fn is_permanent(&self) -> bool {
         false
     }
+
+    fn stop_timeout(&self) -> Duration {
+        self.image.stop_timeout()
+    }
 }
 
 #[async_trait]
-impl<C: ManagedProtocol> RunnableContext<ImageTask<C>> for TaskContext<ImageTask<C>> {
+impl<C: ManagedProtocol, D: ContainerDriver> RunnableContext<ImageTask<C>> for TaskContext<ImageTask<C>, D> {
     async fn initialize(&mut self) {
         self.subscribe_to_events();
     }
 
     fn reconfigure(&mut self, config: Option<&C::Config>) -> bool {
-        self.inner.image.reconfigure(config).unwrap_or_default()
+        let active = self.inner.image.reconfigure(config).unwrap_or_default();
+        let image_name = fqdn(self.inner.image.as_ref());
+        if image_name != self.inner.image_name {
+            log::info!(
+                "Image of the container {} changed from {} to {}",
+                self.inner.container_name,
+                self.inner.image_name,
+                image_name
+            );
+            self.inner.image_name = image_name;
+            self.inner.image_changed = true;
+        }
+        if self.inner.image.restart_required() {
+            log::info!("The config of the container {} changed", self.inner.container_name);
+            self.inner.force_restart = true;
+        }
+        self.check_spec();
+        active
     }
 
     fn process_inner_event(&mut self, event: C::Inner) {
         self.inner.image.on_event(event)
     }
 
+    fn update_image(&mut self) {
+        log::info!("Updating the image {}", self.inner.image_name);
+        self.inner.force_pull = true;
+    }
+
+    fn image_name(&self) -> Option<&str> {
+        Some(&self.inner.image_name)
+    }
+
+    fn pull_image(&mut self, permits: Arc<Semaphore>) {
+        if self.inner.prefetch.is_none() {
+            let prefetch = self.spawn_prefetch(permits);
+            self.inner.prefetch = Some(prefetch);
+        }
+    }
+
     fn process_event(&mut self, event: Event) -> Result<(), Error> {
         self.process_event_impl(event)
     }
@@ -101,11 +185,34 @@ impl<C: ManagedProtocol> RunnableContext<ImageTask<C>> for TaskContext<ImageTask
     async fn update(&mut self) -> Result<(), Error> {
         self.process_update_impl().await
     }
+
+    fn reconcile(&mut self) {
+        // The check could fail without a result
+        self.inner.updates.running = None;
+        match self.status.get() {
+            Status::Active { .. } => {
+                self.inner.resync = true;
+            },
+            Status::PullingImage { .. } | Status::CannotStart => {
+                self.status.set(Status::InitialState);
+            },
+            Status::WaitContainerCreated |
+            Status::WaitContainerStarted |
+            Status::WaitContainerRemoved { .. } |
+            Status::WaitContainerStopped { .. } |
+            Status::WaitContainerKilled { .. } => {
+                // The container could be started already
+                self.inner.adopt = !self.should_be_restarted();
+                self.status.set(Status::CleanDangling);
+            },
+            _ => {},
+        }
+    }
 }
 
-impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
+impl<C: ManagedProtocol, D: ContainerDriver> TaskContext<ImageTask<C>, D> {
     fn should_be_restarted(&self) -> bool {
-        self.force_restart || self.force_pull
+        self.force_restart || self.force_pull || self.image_changed
     }
 }
 
@@ -118,8 +225,24 @@ pub enum Status {
     },
 
     CleanDangling,
-    WaitContainerKilled,
-    WaitContainerRemoved,
+    /// The container was asked to stop and has to exit before the `deadline`.
+    WaitContainerStopped {
+        deadline: Instant,
+    },
+    /// The container was killed and has to die before the `deadline`.
+    WaitContainerKilled {
+        deadline: Instant,
+    },
+    /// The active container exited by itself.
+    ContainerDied,
+    /// The fallback restarts the container after a delay.
+    WaitRestart,
+    /// The container won't be restarted until the task is deactivated or reconfigured.
+    Exited,
+    /// The container was asked to be removed and has to disappear before the `deadline`.
+    WaitContainerRemoved {
+        deadline: Instant,
+    },
     CannotStart,
 
     CreateContainer,
@@ -134,6 +257,7 @@ pub enum Status {
     Active {
         checker: TaskGuard<()>,
         ready: bool,
+        healthy: bool,
     },
 
     DropImage,
@@ -141,7 +265,15 @@ pub enum Status {
 
 impl TaskStatusChecker for Status {
     fn is_ready(&self) -> bool {
-        matches!(self, Self::Active { ready: true, .. })
+        matches!(self, Self::Active {
+            ready: true,
+            healthy: true,
+            ..
+        })
+    }
+
+    fn is_idle(&self) -> bool {
+        matches!(self, Self::Idle | Self::CannotStart)
     }
 }
 
@@ -151,23 +283,24 @@ impl Default for Status {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub enum ContainerState {
-    Running,
-    NotRunning,
-    NotFound,
-}
-
 #[derive(Debug)]
 pub enum Event {
     Destroyed,
     PullingProgress(TaskProgress),
     PullingFailed(String),
+    /// The pull stream is over without errors.
+    Pulled,
     Created,
     Started,
     Killed,
     Terminated,
     CheckerProgress(CheckerEvent),
+    UpdateChecked {
+        image: String,
+        available: bool,
+    },
+    PrefetchProgress(TaskProgress),
+    Prefetched(Result<(), String>),
 }
 
 impl TaskEvent for Event {}
@@ -0,0 +1,199 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::collections::HashMap;
+
+use tari_launchpad_protocol::container::TaskProgress;
+use tokio::time::Instant;
+
+use crate::driver::PullProgress;
+
+#[derive(Debug, Default)]
+struct Layer {
+    /// The size of the compressed layer. It's known when the download starts.
+    size: Option<u64>,
+    downloaded: u64,
+    extracted: u64,
+}
+
+impl Layer {
+    fn size(&self) -> u64 {
+        self.size.unwrap_or_default()
+    }
+
+    fn is_downloading(&self) -> bool {
+        match self.size {
+            Some(size) => self.downloaded < size,
+            None => true,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.size.is_some_and(|size| self.extracted >= size)
+    }
+}
+
+/// Aggregates the progress of all the layers of an image. The runtime reports every layer separately, so a single
+/// record says nothing about the whole image. Every layer is downloaded and extracted, both stages have the same
+/// weight.
+#[derive(Debug)]
+pub struct PullTracker {
+    layers: HashMap<String, Layer>,
+    started_at: Instant,
+    pct: u8,
+    stage: &'static str,
+}
+
+impl Default for PullTracker {
+    fn default() -> Self {
+        Self {
+            layers: HashMap::new(),
+            started_at: Instant::now(),
+            pct: 0,
+            stage: "",
+        }
+    }
+}
+
+impl PullTracker {
+    /// Applies the record of a layer and returns the progress of the image if it changed.
+    pub fn update(&mut self, record: PullProgress) -> Option<TaskProgress> {
+        let id = record.id?;
+        let status = record.status?;
+        let current = record.current.and_then(|value| u64::try_from(value).ok());
+        let total = record.total.and_then(|value| u64::try_from(value).ok());
+        // The other records are about the whole image
+        let layer = match status.as_str() {
+            "Pulling fs layer" | "Waiting" | "Downloading" | "Verifying Checksum" | "Download complete" |
+            "Extracting" | "Pull complete" | "Already exists" => self.layers.entry(id).or_default(),
+            _ => return None,
+        };
+        match status.as_str() {
+            "Downloading" => {
+                layer.size = total.or(layer.size);
+                layer.downloaded = current.unwrap_or(layer.downloaded);
+            },
+            "Verifying Checksum" | "Download complete" => {
+                layer.downloaded = layer.size.unwrap_or(layer.downloaded);
+            },
+            "Extracting" => {
+                layer.size = layer.size.or(total);
+                layer.downloaded = layer.size();
+                layer.extracted = current.unwrap_or(layer.extracted);
+            },
+            "Pull complete" => {
+                let size = layer.size.unwrap_or(layer.downloaded);
+                layer.size = Some(size);
+                layer.downloaded = size;
+                layer.extracted = size;
+            },
+            "Already exists" => {
+                layer.size = Some(0);
+            },
+            _ => {},
+        }
+        self.progress()
+    }
+
+    fn progress(&mut self) -> Option<TaskProgress> {
+        let total: u64 = self.layers.values().map(Layer::size).sum();
+        let downloaded: u64 = self
+            .layers
+            .values()
+            .map(|layer| layer.downloaded.min(layer.size()))
+            .sum();
+        let extracted: u64 = self
+            .layers
+            .values()
+            .map(|layer| layer.extracted.min(layer.size()))
+            .sum();
+        let done = self.layers.values().all(Layer::is_done);
+        let pct = if done {
+            100
+        } else if total > 0 {
+            // The sizes of the waiting layers are unknown, so the value never reaches 100 before the end
+            let pct = (downloaded + extracted) * 100 / (total * 2);
+            u8::try_from(pct.min(99)).unwrap_or(99)
+        } else {
+            0
+        };
+        let downloading = self.layers.values().any(Layer::is_downloading);
+        let stage = if done {
+            "Pulled"
+        } else if downloading {
+            "Downloading..."
+        } else {
+            "Extracting..."
+        };
+        // The progress is monotonic even if new layers appear
+        let pct = pct.max(self.pct);
+        if pct == self.pct && stage == self.stage {
+            return None;
+        }
+        self.pct = pct;
+        self.stage = stage;
+        let eta = if downloading { self.eta(downloaded, total) } else { None };
+        let progress = TaskProgress {
+            pct,
+            stage: stage.to_string(),
+            total: Some(total).filter(|total| *total > 0),
+            eta,
+        };
+        Some(progress)
+    }
+
+    /// Estimates the seconds left to download the layers with the average speed.
+    fn eta(&self, downloaded: u64, total: u64) -> Option<u64> {
+        let elapsed = self.started_at.elapsed().as_secs_f64();
+        if downloaded == 0 || elapsed == 0.0 {
+            return None;
+        }
+        let speed = downloaded as f64 / elapsed;
+        let left = total.saturating_sub(downloaded) as f64;
+        Some((left / speed).ceil() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, status: &str, current: Option<i64>, total: Option<i64>) -> PullProgress {
+        PullProgress {
+            id: Some(id.to_string()),
+            status: Some(status.to_string()),
+            current,
+            total,
+        }
+    }
+
+    #[test]
+    fn test_progress_aggregated() {
+        let mut tracker = PullTracker::default();
+        assert!(tracker
+            .update(record("latest", "Pulling from tari/app", None, None))
+            .is_none());
+        tracker.update(record("a", "Pulling fs layer", None, None));
+        tracker.update(record("b", "Pulling fs layer", None, None));
+
+        let progress = tracker.update(record("a", "Downloading", Some(50), Some(100))).unwrap();
+        assert_eq!(progress.pct, 25);
+        assert_eq!(progress.total, Some(100));
+        assert_eq!(progress.stage, "Downloading...");
+
+        // The size of the new layer doesn't move the progress back
+        let progress = tracker.update(record("b", "Downloading", Some(0), Some(300)));
+        assert!(progress.is_none());
+
+        tracker.update(record("a", "Download complete", None, None));
+        tracker.update(record("b", "Download complete", None, None));
+        let progress = tracker.update(record("a", "Pull complete", None, None)).unwrap();
+        assert_eq!(progress.pct, 62);
+        assert_eq!(progress.stage, "Extracting...");
+        assert_eq!(progress.eta, None);
+
+        let progress = tracker.update(record("b", "Pull complete", None, None)).unwrap();
+        assert_eq!(progress.pct, 100);
+        assert_eq!(progress.total, Some(400));
+    }
+}
@@ -21,20 +21,43 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
+use std::mem;
+
 use anyhow::Error;
 use tari_launchpad_protocol::container::{TaskProgress, TaskStatus};
+use tokio::time::{Duration, Instant};
+
+use super::{ImageTask, Status};
+use crate::{
+    config::ManagedProtocol,
+    driver::{ContainerDriver, ContainerState},
+    status::Fallback,
+    task::TaskContext,
+    utils::backoff,
+};
 
-use super::{ContainerState, ImageTask, Status};
-use crate::{config::ManagedProtocol, task::TaskContext};
+/// How long a killed container has to die before its state is checked again.
+const KILL_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a removed container has to disappear before its state is checked again.
+const REMOVE_TIMEOUT: Duration = Duration::from_secs(10);
+/// The delay before the first restart. It's doubled for every next attempt.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+/// The container that worked that long before the exit is not considered crash looping.
+const STABLE_PERIOD: Duration = Duration::from_secs(60);
+/// How often the busy ports of the container are checked again.
+const PORT_RETRY_DELAY: Duration = Duration::from_secs(5);
 
-impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
+impl<C: ManagedProtocol, D: ContainerDriver> TaskContext<ImageTask<C>, D> {
     pub async fn process_update_impl(&mut self) -> Result<(), Error> {
+        self.schedule_update_check();
         match self.status.get() {
             Status::InitialState => self.do_initial_state().await,
             Status::PullingImage { .. } => self.do_pulling().await,
             Status::CleanDangling => self.do_clean_dangling().await,
-            Status::WaitContainerKilled => self.do_wait_container_killed().await,
-            Status::WaitContainerRemoved => self.do_wait_container_removed().await,
+            Status::WaitContainerStopped { .. } => self.do_wait_container_stopped().await,
+            Status::WaitContainerKilled { .. } => self.do_wait_container_killed().await,
+            Status::WaitContainerRemoved { .. } => self.do_wait_container_removed().await,
             Status::CannotStart => self.abort().await,
             Status::Idle => self.do_idle().await,
             Status::CreateContainer => self.do_create_container().await,
@@ -42,10 +65,29 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
             Status::StartContainer => self.do_start_container().await,
             Status::WaitContainerStarted => self.do_wait_container_started().await,
             Status::Active { .. } => self.do_active().await,
+            Status::ContainerDied => self.do_container_died().await,
+            Status::WaitRestart => self.do_wait_restart().await,
+            Status::Exited => self.do_exited().await,
             Status::DropImage => self.do_drop_image().await,
         }
     }
 
+    /// Starts a check of the updates periodically when the image is pulled.
+    fn schedule_update_check(&mut self) {
+        let Some(checker) = self.inner.updates.checker.clone() else {
+            return;
+        };
+        let pulled = matches!(self.status.get(), Status::Idle | Status::Active { .. });
+        if pulled && self.inner.updates.running.is_none() && Instant::now() >= self.inner.updates.next_check {
+            self.inner.updates.next_check = Instant::now() + checker.interval();
+            let check = self.spawn_update_check(checker);
+            self.inner.updates.running = Some(check);
+        }
+        if pulled {
+            self.status.wake_at(self.inner.updates.next_check);
+        }
+    }
+
     async fn do_initial_state(&mut self) -> Result<(), Error> {
         self.update_task_status(TaskStatus::Inactive)?;
 
@@ -69,6 +111,12 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
 
     fn start_pulling(&mut self) -> Result<(), Error> {
         log::debug!("Image {} doesn't exist. Pulling.", self.inner.image_name);
+        // The result of a running check is outdated
+        self.inner.updates.running = None;
+        if self.inner.updates.available {
+            self.inner.updates.available = false;
+            self.sender().send_update_available(false)?;
+        }
         let progress = TaskProgress::new("Pulling...");
         self.update_task_status(TaskStatus::Progress(progress))?;
         let progress = self.pull();
@@ -76,6 +124,7 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
         Ok(())
     }
 
+    /// Checks the image when the pull is finished.
     async fn do_pulling(&mut self) -> Result<(), Error> {
         if self.image_exists().await {
             // Just loaded, container can't be exist
@@ -86,7 +135,7 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
     }
 
     /// Removes containers that shouldn't be there, for example after a crash, or if the user started a container
-    /// manually in docker. If the container is still running, we'll try and kill it first, otherwise we'll just
+    /// manually in docker. If the container is still running, we'll try and stop it first, otherwise we'll just
     /// remove it.
     async fn do_clean_dangling(&mut self) -> Result<(), Error> {
         log::debug!(
@@ -94,14 +143,26 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
             self.inner.container_name
         );
         let state = self.container_state().await;
+        if self.inner.adopt && state == ContainerState::Running {
+            if self.activation_pending() {
+                // The spec of the container depends on the config and on the dependencies
+                return Ok(());
+            }
+            self.inner.adopt = false;
+            if self.try_adopt().await? {
+                return Ok(());
+            }
+        }
+        self.inner.adopt = false;
         match state {
             ContainerState::Running => {
                 log::debug!(
-                    "[Clean dangling] Container {} is running. Terminating it.",
+                    "[Clean dangling] Container {} is running. Stopping it.",
                     self.inner.container_name
                 );
-                self.try_kill_container().await?;
-                self.status.set(Status::WaitContainerKilled);
+                self.try_stop_container().await?;
+                let deadline = Instant::now() + self.inner.image.stop_timeout();
+                self.status.set(Status::WaitContainerStopped { deadline });
             },
             ContainerState::NotRunning => {
                 log::debug!(
@@ -109,13 +170,15 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
                     self.inner.container_name
                 );
                 self.try_remove_container().await?;
-                self.status.set(Status::WaitContainerRemoved);
+                let deadline = Instant::now() + REMOVE_TIMEOUT;
+                self.status.set(Status::WaitContainerRemoved { deadline });
             },
             ContainerState::NotFound => {
                 log::debug!(
                     "[Clean dangling] Container {} doesn't exist.",
                     self.inner.container_name
                 );
+                self.inner.spec = None;
                 self.status.set(Status::Idle);
                 self.update_task_status(TaskStatus::Inactive)?;
             },
@@ -123,13 +186,77 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
         Ok(())
     }
 
+    /// Takes over the container of the previous run if it has to be active with the same spec.
+    async fn try_adopt(&mut self) -> Result<bool, Error> {
+        if !self.should_be_active() || self.inner.force_pull || !self.is_adoptable().await {
+            return Ok(false);
+        }
+        log::info!(
+            "[Clean dangling] Container {} is running with the same spec. Adopting it.",
+            self.inner.container_name
+        );
+        // The first config could set the flags, but the spec is the same
+        self.inner.force_restart = false;
+        self.inner.image_changed = false;
+        self.inner.spec = self.container_spec().ok();
+        self.update_task_status(TaskStatus::Pending)?;
+        self.activate();
+        Ok(true)
+    }
+
+    async fn do_wait_container_stopped(&mut self) -> Result<(), Error> {
+        if let Status::WaitContainerStopped { deadline } = *self.status.get() {
+            if Instant::now() >= deadline {
+                log::warn!(
+                    "Container {} is not stopped in {:?}. Killing it.",
+                    self.inner.container_name,
+                    self.inner.image.stop_timeout()
+                );
+                if let Err(err) = self.try_kill_container().await {
+                    // It could exit right now, the state will be checked again
+                    log::error!("Can't kill the container {}: {}", self.inner.container_name, err);
+                    self.status.set(Status::CleanDangling);
+                } else {
+                    let deadline = Instant::now() + KILL_TIMEOUT;
+                    self.status.set(Status::WaitContainerKilled { deadline });
+                }
+            } else {
+                self.status.wake_at(deadline);
+            }
+        }
+        Ok(())
+    }
+
     async fn do_wait_container_killed(&mut self) -> Result<(), Error> {
-        // TODO: Wait interval
+        if let Status::WaitContainerKilled { deadline } = *self.status.get() {
+            if Instant::now() >= deadline {
+                log::warn!(
+                    "Container {} is not dead in {:?}. Checking it again.",
+                    self.inner.container_name,
+                    KILL_TIMEOUT
+                );
+                self.status.set(Status::CleanDangling);
+            } else {
+                self.status.wake_at(deadline);
+            }
+        }
         Ok(())
     }
 
     async fn do_wait_container_removed(&mut self) -> Result<(), Error> {
-        // TODO: Wait interval
+        if let Status::WaitContainerRemoved { deadline } = *self.status.get() {
+            if Instant::now() >= deadline {
+                // The event could be lost
+                log::warn!(
+                    "Container {} is not removed in {:?}. Checking it again.",
+                    self.inner.container_name,
+                    REMOVE_TIMEOUT
+                );
+                self.status.set(Status::CleanDangling);
+            } else {
+                self.status.wake_at(deadline);
+            }
+        }
         Ok(())
     }
 
@@ -138,7 +265,12 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
     }
 
     async fn do_idle(&mut self) -> Result<(), Error> {
-        if self.force_pull {
+        if self.image_changed {
+            // Pulls the new image if it's missing
+            self.image_changed = false;
+            self.status.set(Status::InitialState);
+            Ok(())
+        } else if self.force_pull {
             self.force_pull = false;
             self.status.set(Status::DropImage);
             let progress = TaskProgress::new("Removing image...");
@@ -157,6 +289,25 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
 
     async fn do_create_container(&mut self) -> Result<(), Error> {
         log::debug!("Trying to create container {} ...", self.inner.container_name);
+        if let Some(port) = self.busy_port().await {
+            log::warn!(
+                "Can't create container {}: the port {} is in use",
+                self.inner.container_name,
+                port
+            );
+            let reason = format!("The port {} is already in use", port);
+            self.update_task_status(TaskStatus::Failed(reason))?;
+            // Another port in the settings changes the spec and restarts the task at once
+            self.inner.spec = self.container_spec().ok();
+            self.status.set(Status::Exited);
+            // The port could be released by another process
+            let fallback = Fallback {
+                when: Instant::now() + PORT_RETRY_DELAY,
+                next_status: Status::Idle,
+            };
+            self.status.set_fallback(fallback);
+            return Ok(());
+        }
         // TODO: Process the result as well
         self.try_create_container().await?;
         self.status.set(Status::WaitContainerCreated);
@@ -172,7 +323,8 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
         if let Err(err) = self.try_start_container().await {
             self.sender().send_error(err.to_string())?;
             self.try_remove_container().await?;
-            self.status.set(Status::WaitContainerRemoved);
+            let deadline = Instant::now() + REMOVE_TIMEOUT;
+            self.status.set(Status::WaitContainerRemoved { deadline });
         } else {
             self.status.set(Status::WaitContainerStarted);
             self.update_task_status(TaskStatus::Pending)?;
@@ -181,7 +333,89 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
     }
 
     async fn do_active(&mut self) -> Result<(), Error> {
+        if mem::take(&mut self.inner.resync) {
+            if self.container_state().await == ContainerState::Running {
+                log::info!("Container {} is still running", self.inner.container_name);
+                self.resubscribe();
+            } else {
+                // The event was lost
+                log::warn!(
+                    "Container {} exited while the daemon was unavailable",
+                    self.inner.container_name
+                );
+                self.status.set(Status::ContainerDied);
+                return Ok(());
+            }
+        }
+        if !self.should_be_active() || self.should_be_restarted() {
+            self.inner.restarts = 0;
+            self.status.set(Status::CleanDangling);
+        }
+        Ok(())
+    }
+
+    async fn do_container_died(&mut self) -> Result<(), Error> {
+        let exit_code = self.driver.exit_code(&self.inner.container_name).await;
+        let stable = self
+            .inner
+            .started_at
+            .map(|started_at| started_at.elapsed() >= STABLE_PERIOD)
+            .unwrap_or_default();
+        if stable {
+            self.inner.restarts = 0;
+        }
+        let policy = self.inner.image.restart_policy();
+        if !self.should_be_active() {
+            // It's not needed anymore
+            self.inner.restarts = 0;
+            self.status.set(Status::CleanDangling);
+        } else if policy.should_restart(exit_code, self.inner.restarts) {
+            self.inner.restarts += 1;
+            let delay = backoff(self.inner.restarts, RESTART_DELAY, MAX_RESTART_DELAY);
+            log::info!(
+                "Restarting container {} in {:?} (attempt {})",
+                self.inner.container_name,
+                delay,
+                self.inner.restarts
+            );
+            self.sender().send_restart(self.inner.restarts, delay, exit_code)?;
+            let progress = TaskProgress::new(format!("Restarting in {}s...", delay.as_secs()));
+            self.update_task_status(TaskStatus::Progress(progress))?;
+            self.status.set(Status::WaitRestart);
+            let fallback = Fallback {
+                when: Instant::now() + delay,
+                next_status: Status::CleanDangling,
+            };
+            self.status.set_fallback(fallback);
+        } else {
+            log::warn!(
+                "Container {} exited with the code {:?} and won't be restarted",
+                self.inner.container_name,
+                exit_code
+            );
+            let status = match exit_code {
+                Some(0) => TaskStatus::Inactive,
+                Some(code) => TaskStatus::Failed(format!("The container exited with the code {}", code)),
+                None => TaskStatus::Failed("The container exited".into()),
+            };
+            self.update_task_status(status)?;
+            self.status.set(Status::Exited);
+        }
+        Ok(())
+    }
+
+    async fn do_wait_restart(&mut self) -> Result<(), Error> {
+        if !self.should_be_active() {
+            self.inner.restarts = 0;
+            self.status.set(Status::CleanDangling);
+        }
+        Ok(())
+    }
+
+    async fn do_exited(&mut self) -> Result<(), Error> {
+        // The exited container is kept to check its logs
         if !self.should_be_active() || self.should_be_restarted() {
+            self.inner.restarts = 0;
             self.status.set(Status::CleanDangling);
         }
         Ok(())
@@ -192,6 +426,12 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
     }
 
     async fn do_drop_image(&mut self) -> Result<(), Error> {
-        self.try_remove_image().await
+        if let Err(err) = self.try_remove_image().await {
+            // The existing image will be used
+            log::error!("Can't remove the image {}: {}", self.inner.image_name, err);
+            self.sender().send_error(err.to_string())?;
+        }
+        self.status.set(Status::InitialState);
+        Ok(())
     }
 }
@@ -21,12 +21,17 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
+pub mod archive;
 pub mod config;
+pub mod driver;
 mod error;
 mod forwarder;
+mod hub;
 pub mod image;
 pub mod network;
+pub mod registry;
 mod scope;
+mod snapshot;
 mod status;
 mod task;
 pub mod utils;
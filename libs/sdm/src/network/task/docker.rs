@@ -21,104 +21,33 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::collections::HashMap;
-
 use anyhow::Error;
-use bollard::{
-    models::{EventMessage, EventMessageTypeEnum},
-    network::{CreateNetworkOptions, InspectNetworkOptions},
-    system::EventsOptions,
-};
-use futures::TryStreamExt;
 
-use super::{Event, NetworkTask};
+use super::NetworkTask;
 use crate::{
     config::ManagedProtocol,
-    forwarder::{Converter, Forwarder},
+    driver::{ContainerDriver, EventConv, ResourceKind},
+    forwarder::Forwarder,
     task::TaskContext,
 };
 
-impl<C: ManagedProtocol> TaskContext<NetworkTask<C>> {
+impl<C: ManagedProtocol, D: ContainerDriver> TaskContext<NetworkTask<C>, D> {
     pub fn subscribe_to_events(&mut self) {
-        let mut type_filter = HashMap::new();
-        type_filter.insert("type".to_string(), vec!["network".to_string()]);
-        type_filter.insert("network".to_string(), vec![self.inner.network_name.clone()]);
-        let opts = EventsOptions {
-            since: None,
-            until: None,
-            filters: type_filter,
-        };
-        let stream = self.driver.events(Some(opts)).map_err(Error::from);
+        let stream = self.resource_events(ResourceKind::Network, &self.inner.network_name);
         let sender = self.sender().get_direct().clone();
-        let conv = EventConv {
-            // TODO: Name is not necessary here
-            name: self.inner.network_name.clone(),
-        };
-        let handle = Forwarder::start(stream, conv, sender);
+        let handle = Forwarder::start(stream, EventConv, sender);
         self.inner.events = Some(handle);
     }
 
     pub async fn network_exists(&mut self) -> bool {
-        let opts = InspectNetworkOptions {
-            verbose: false,
-            scope: "local",
-        };
-        self.driver
-            .inspect_network(&self.inner.network_name, Some(opts))
-            .await
-            .is_ok()
+        self.driver.network_exists(&self.inner.network_name).await
     }
 
     pub async fn try_create_network(&mut self) -> Result<(), Error> {
-        let options = CreateNetworkOptions {
-            name: self.inner.network_name.as_ref(),
-            check_duplicate: true,
-            driver: "bridge",
-            internal: false,
-            attachable: false,
-            ingress: false,
-            ipam: Default::default(),
-            enable_ipv6: false,
-            options: Default::default(),
-            labels: Default::default(),
-        };
-        self.driver.create_network(options).await?;
-        // TODO: Check warnings...
-        Ok(())
+        self.driver.create_network(&self.inner.network_name).await
     }
 
     pub async fn try_remove_network(&mut self) -> Result<(), Error> {
-        self.driver.remove_network(&self.inner.network_name).await?;
-        Ok(())
-    }
-}
-
-struct EventConv {
-    name: String,
-}
-
-impl Converter<EventMessage, Event> for EventConv {
-    fn convert(&self, res: Result<EventMessage, Error>) -> Option<Event> {
-        if let Ok(EventMessage {
-            typ: Some(typ),
-            action: Some(action),
-            actor: Some(actor),
-            ..
-        }) = res
-        {
-            if let Some(attributes) = actor.attributes {
-                if let Some(name) = attributes.get("name") {
-                    if self.name == *name {
-                        // TODO: Check the name
-                        if let EventMessageTypeEnum::NETWORK = typ {
-                            return action.try_into().ok();
-                        }
-                    } else {
-                        log::error!("Message for other network {}, but expected {}", name, self.name);
-                    }
-                }
-            }
-        }
-        None
+        self.driver.remove_network(&self.inner.network_name).await
     }
 }
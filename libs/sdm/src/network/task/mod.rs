@@ -31,6 +31,7 @@ use async_trait::async_trait;
 use super::ManagedNetwork;
 use crate::{
     config::ManagedProtocol,
+    driver::ContainerDriver,
     error::ParseError,
     task::{RunnableContext, RunnableTask, TaskContext, TaskEvent, TaskStatusChecker},
     utils::TaskGuard,
@@ -69,7 +70,7 @@ impl<C: ManagedProtocol> RunnableTask for NetworkTask<C> {
 }
 
 #[async_trait]
-impl<C: ManagedProtocol> RunnableContext<NetworkTask<C>> for TaskContext<NetworkTask<C>> {
+impl<C: ManagedProtocol, D: ContainerDriver> RunnableContext<NetworkTask<C>> for TaskContext<NetworkTask<C>, D> {
     async fn initialize(&mut self) {
         self.subscribe_to_events();
     }
@@ -89,6 +90,10 @@ impl<C: ManagedProtocol> RunnableContext<NetworkTask<C>> for TaskContext<Network
     async fn update(&mut self) -> Result<(), Error> {
         self.process_update_impl().await
     }
+
+    fn reconcile(&mut self) {
+        self.status.set(Status::InitialState);
+    }
 }
 
 #[derive(Debug)]
@@ -105,6 +110,10 @@ impl TaskStatusChecker for Status {
     fn is_ready(&self) -> bool {
         matches!(self, Self::Active)
     }
+
+    fn is_idle(&self) -> bool {
+        matches!(self, Self::Inactive)
+    }
 }
 
 impl Default for Status {
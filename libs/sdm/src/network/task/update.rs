@@ -25,9 +25,9 @@ use anyhow::Error;
 use tari_launchpad_protocol::container::TaskStatus;
 
 use super::{NetworkTask, Status};
-use crate::{config::ManagedProtocol, task::TaskContext};
+use crate::{config::ManagedProtocol, driver::ContainerDriver, task::TaskContext};
 
-impl<C: ManagedProtocol> TaskContext<NetworkTask<C>> {
+impl<C: ManagedProtocol, D: ContainerDriver> TaskContext<NetworkTask<C>, D> {
     pub async fn process_update_impl(&mut self) -> Result<(), Error> {
         match self.status.get() {
             Status::InitialState => self.do_initial_state().await,
@@ -40,8 +40,14 @@ impl<C: ManagedProtocol> TaskContext<NetworkTask<C>> {
     }
 
     async fn do_initial_state(&mut self) -> Result<(), Error> {
-        self.update_task_status(TaskStatus::Inactive)?;
-        self.status.set(Status::Cleanup);
+        if self.network_exists().await {
+            // The containers left running by the previous run are attached to it
+            self.status.set(Status::Active);
+            self.update_task_status(TaskStatus::Active)?;
+        } else {
+            self.status.set(Status::Inactive);
+            self.update_task_status(TaskStatus::Inactive)?;
+        }
         Ok(())
     }
 
@@ -75,7 +81,7 @@ impl<C: ManagedProtocol> TaskContext<NetworkTask<C>> {
     }
 
     async fn do_active(&mut self) -> Result<(), Error> {
-        if !self.should_be_active() {
+        if self.is_configured() && !self.should_be_active() {
             self.status.set(Status::Cleanup);
         }
         Ok(())
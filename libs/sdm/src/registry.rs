@@ -0,0 +1,56 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{fmt, sync::Arc, time::Duration};
+
+use anyhow::Error;
+use async_trait::async_trait;
+
+use crate::driver::ContainerDriver;
+
+/// A source of the manifests of the images. The runtime implements it through the daemon, but it could be any
+/// registry API as well.
+#[async_trait]
+pub trait RegistryClient: Send + Sync + 'static {
+    /// The digest of the manifest of the `image` in the registry, e.g. `sha256:...`.
+    async fn manifest_digest(&self, image: &str) -> Result<String, Error>;
+}
+
+/// Compares the local images with the registry periodically.
+#[derive(Clone)]
+pub struct UpdateChecker {
+    registry: Arc<dyn RegistryClient>,
+    interval: Duration,
+}
+
+impl fmt::Debug for UpdateChecker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UpdateChecker")
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+impl UpdateChecker {
+    pub fn new<R: RegistryClient>(registry: R, interval: Duration) -> Self {
+        Self {
+            registry: Arc::new(registry),
+            interval,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Checks whether the registry has another version of the `image`. The images without digests were built
+    /// locally or not pulled yet, so they are never outdated.
+    pub async fn is_outdated<D: ContainerDriver>(&self, driver: &D, image: &str) -> Result<bool, Error> {
+        let local = driver.image_digests(image).await;
+        if local.is_empty() {
+            return Ok(false);
+        }
+        let remote = self.registry.manifest_digest(image).await?;
+        Ok(!local.contains(&remote))
+    }
+}
@@ -21,21 +21,45 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::sync::Arc;
+use std::{path::Path, sync::Arc, time::Duration};
 
 use anyhow::{anyhow, Error};
-use bollard::Docker;
-use tari_launchpad_protocol::container::{TaskDelta, TaskId, TaskState};
-use tokio::sync::{broadcast, mpsc};
+use tari_launchpad_protocol::{
+    container::{DaemonState, TaskDelta, TaskId, TaskState},
+    settings::ContainerEndpoint,
+};
+use tokio::{
+    sync::{broadcast, mpsc, watch, Semaphore},
+    task::JoinHandle,
+    time::timeout,
+};
 
 use crate::{
+    archive,
     config::ManagedProtocol,
+    driver::{ContainerDriver, DockerDriver, ResourceKind},
+    hub::EventsHub,
     image::{ImageTask, ManagedContainer},
     network::{ManagedNetwork, NetworkTask},
-    task::{ManagedTask, SdmTaskRunner},
+    registry::{RegistryClient, UpdateChecker},
+    snapshot::ScopeSnapshot,
+    task::{ManagedTask, RunnableContext, RunnableTask, SdmTaskRunner, TaskContext},
     volume::{ManagedVolume, VolumeTask},
 };
 
+/// How long a task has to stop by itself on shutdown in addition to the grace period of its resource.
+const STOP_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long a task has to stop after the resource was removed forcibly.
+const FORCED_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a runner has to finish after the termination request.
+const TERMINATE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long the runners have to reply to a request.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often the images are compared with the registry.
+const UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// The requests of the tasks are broadcasted to every task. A task that lags behind loses the oldest of them.
+const REQUESTS_CAPACITY: usize = 1024;
+
 #[derive(Debug)]
 pub struct ReportEnvelope<C: ManagedProtocol> {
     pub task_id: TaskId,
@@ -49,12 +73,33 @@ pub enum Report<C: ManagedProtocol> {
     Extras(C::Outer),
 }
 
-pub struct SdmScope<C: ManagedProtocol> {
+/// A spawned runner of a task.
+struct TaskHandle {
+    task_id: TaskId,
+    deps: Vec<TaskId>,
+    /// The name of the resource in the runtime.
+    name: String,
+    kind: ResourceKind,
+    permanent: bool,
+    stop_timeout: Duration,
+    stopped: watch::Receiver<bool>,
+    handle: JoinHandle<()>,
+}
+
+pub struct SdmScope<C: ManagedProtocol, D: ContainerDriver = DockerDriver> {
     scope: String,
-    docker: Docker,
+    driver: D,
     reporter: mpsc::UnboundedReceiver<ReportEnvelope<C>>,
     report_sender: mpsc::UnboundedSender<ReportEnvelope<C>>,
     sender: broadcast::Sender<ControlEvent<C>>,
+    /// The last state of the requests for the tasks that missed them.
+    snapshot: ScopeSnapshot<C>,
+    tasks: Vec<TaskHandle>,
+    updates: Option<UpdateChecker>,
+    /// Is started with the first task.
+    hub: Option<EventsHub>,
+    /// The connection to the daemon monitored by the hub.
+    daemon: Arc<watch::Sender<DaemonState>>,
 }
 
 // TODO: Move to the `task` mod?
@@ -70,6 +115,25 @@ pub enum ControlEvent<C: ManagedProtocol> {
         task_id: TaskId,
     },
     InnerEvent(C::Inner),
+    /// Releases the resources of the task and ignores the config after that.
+    Stop {
+        task_id: TaskId,
+    },
+    /// Pulls the image of the task again and restarts its container.
+    UpdateImage {
+        task_id: TaskId,
+    },
+    /// Pulls the images of the tasks, or of all the tasks if `None`. The `permits` limit the parallel pulls.
+    PullImages {
+        tasks: Option<Vec<TaskId>>,
+        permits: Arc<Semaphore>,
+    },
+    /// Asks the tasks with images to send the names of the images with the current config.
+    ListImages {
+        images: mpsc::UnboundedSender<String>,
+    },
+    /// Finishes all the runners.
+    Terminate,
 }
 
 impl<C: ManagedProtocol> Clone for ControlEvent<C> {
@@ -84,39 +148,68 @@ impl<C: ManagedProtocol> Clone for ControlEvent<C> {
                 task_id: task_id.clone(),
             },
             Self::InnerEvent(inner) => Self::InnerEvent(inner.clone()),
+            Self::Stop { task_id } => Self::Stop {
+                task_id: task_id.clone(),
+            },
+            Self::UpdateImage { task_id } => Self::UpdateImage {
+                task_id: task_id.clone(),
+            },
+            Self::PullImages { tasks, permits } => Self::PullImages {
+                tasks: tasks.clone(),
+                permits: permits.clone(),
+            },
+            Self::ListImages { images } => Self::ListImages { images: images.clone() },
+            Self::Terminate => Self::Terminate,
         }
     }
 }
 
 impl<C: ManagedProtocol> SdmScope<C> {
-    pub fn connect(scope: &str) -> Result<Self, Error> {
-        let docker = Docker::connect_with_local_defaults()?;
+    pub async fn connect(scope: &str, endpoint: &ContainerEndpoint) -> Result<Self, Error> {
+        let driver = DockerDriver::connect(endpoint).await?;
+        let mut scope = Self::with_driver(scope, driver.clone());
+        scope.check_updates(driver, UPDATE_CHECK_INTERVAL);
+        Ok(scope)
+    }
+}
+
+impl<C: ManagedProtocol, D: ContainerDriver> SdmScope<C, D> {
+    pub fn with_driver(scope: &str, driver: D) -> Self {
         // TODO: Use `rx` later to control entries
-        let (req_tx, _req_rx) = broadcast::channel(16);
+        let (req_tx, _req_rx) = broadcast::channel(REQUESTS_CAPACITY);
         let (rep_tx, rep_rx) = mpsc::unbounded_channel();
-        Ok(Self {
+        Self {
             scope: scope.to_string(),
-            docker,
+            driver,
             reporter: rep_rx,
             report_sender: rep_tx,
             sender: req_tx,
-        })
+            snapshot: ScopeSnapshot::default(),
+            tasks: Vec::new(),
+            updates: None,
+            hub: None,
+            daemon: Arc::new(watch::channel(DaemonState::default()).0),
+        }
+    }
+
+    /// Compares the images with the `registry` periodically and reports the tasks that can be updated. It applies to
+    /// the images added after the call.
+    pub fn check_updates<R: RegistryClient>(&mut self, registry: R, interval: Duration) {
+        self.updates = Some(UpdateChecker::new(registry, interval));
+    }
+
+    /// Tracks the connection to the daemon. The tasks check their resources again when it's restored.
+    pub fn daemon_state(&self) -> watch::Receiver<DaemonState> {
+        self.daemon.subscribe()
     }
 
     pub fn add_image<I>(&mut self, entry: I) -> Result<(), Error>
     where
         I: ManagedContainer<Protocol = C> + ManagedTask,
     {
-        // TODO: DRY!
         let entry = Box::new(entry);
-        let inner = ImageTask::new(&self.scope, entry);
-        let runner = SdmTaskRunner::new::<I>(
-            self.sender.clone(),
-            self.report_sender.clone(),
-            inner,
-            self.docker.clone(),
-        );
-        tokio::spawn(runner.entrypoint());
+        let inner = ImageTask::new(&self.scope, entry, self.updates.clone());
+        self.spawn::<I, _>(inner, ResourceKind::Container);
         Ok(())
     }
 
@@ -124,16 +217,9 @@ impl<C: ManagedProtocol> SdmScope<C> {
     where
         N: ManagedNetwork<Protocol = C> + ManagedTask,
     {
-        // TODO: DRY!
         let entry = Box::new(entry);
         let inner = NetworkTask::new(&self.scope, entry);
-        let runner = SdmTaskRunner::new::<N>(
-            self.sender.clone(),
-            self.report_sender.clone(),
-            inner,
-            self.docker.clone(),
-        );
-        tokio::spawn(runner.entrypoint());
+        self.spawn::<N, _>(inner, ResourceKind::Network);
         Ok(())
     }
 
@@ -141,17 +227,46 @@ impl<C: ManagedProtocol> SdmScope<C> {
     where
         V: ManagedVolume<Protocol = C> + ManagedTask,
     {
-        // TODO: DRY!
         let entry = Box::new(entry);
         let inner = VolumeTask::new(&self.scope, entry);
-        let runner = SdmTaskRunner::new::<V>(
+        self.spawn::<V, _>(inner, ResourceKind::Volume);
+        Ok(())
+    }
+
+    fn spawn<M, R>(&mut self, inner: R, kind: ResourceKind)
+    where
+        M: ManagedTask,
+        R: RunnableTask<Protocol = C>,
+        TaskContext<R, D>: RunnableContext<R>,
+    {
+        let name = inner.name().to_string();
+        let permanent = inner.is_permanent();
+        let stop_timeout = inner.stop_timeout();
+        let hub = self
+            .hub
+            .get_or_insert_with(|| EventsHub::start(&self.scope, self.driver.clone(), self.daemon.clone()))
+            .clone();
+        let runner = SdmTaskRunner::new::<M>(
             self.sender.clone(),
             self.report_sender.clone(),
             inner,
-            self.docker.clone(),
+            self.driver.clone(),
+            hub,
+            self.snapshot.clone(),
         );
-        tokio::spawn(runner.entrypoint());
-        Ok(())
+        let stopped = runner.subscribe_stopped();
+        let handle = tokio::spawn(runner.entrypoint());
+        let task = TaskHandle {
+            task_id: M::id(),
+            deps: M::deps(),
+            name,
+            kind,
+            permanent,
+            stop_timeout,
+            stopped,
+            handle,
+        };
+        self.tasks.push(task);
     }
 
     pub fn set_config(&mut self, config: Option<C::Config>) -> Result<(), Error> {
@@ -160,16 +275,171 @@ impl<C: ManagedProtocol> SdmScope<C> {
         self.send(req)
     }
 
-    fn send(&mut self, req: ControlEvent<C>) -> Result<(), Error> {
+    fn send(&self, req: ControlEvent<C>) -> Result<(), Error> {
+        self.snapshot.record(&req);
         self.sender
             .send(req)
             .map(drop)
             .map_err(|req| anyhow!("Can't send a request: {:?}", req))
     }
 
+    /// Pulls the image of the task again and restarts its container.
+    pub fn update_image(&self, task_id: TaskId) -> Result<(), Error> {
+        self.send(ControlEvent::UpdateImage { task_id })
+    }
+
+    /// Pulls the images of the tasks, or of all the tasks if `None`, without starting the containers. No more than
+    /// `parallelism` images are pulled at the same time.
+    pub fn pull_images(&self, tasks: Option<Vec<TaskId>>, parallelism: usize) -> Result<(), Error> {
+        let permits = Arc::new(Semaphore::new(parallelism.max(1)));
+        self.send(ControlEvent::PullImages { tasks, permits })
+    }
+
+    /// The number of the CPUs of the host the containers run on.
+    pub async fn host_cpus(&self) -> Option<usize> {
+        self.driver.host_cpus().await
+    }
+
+    /// The port of the host the `port` of the running container of the task is published on.
+    pub async fn host_port(&self, task_id: &TaskId, port: u16) -> Option<u16> {
+        let task = self
+            .tasks
+            .iter()
+            .find(|task| task.kind == ResourceKind::Container && &task.task_id == task_id)?;
+        self.driver.host_port(&task.name, port).await
+    }
+
+    /// The images of the containers with the current config. The runners process the requests in order, so the
+    /// config that was set before is taken into account.
+    pub async fn images(&self) -> Result<Vec<String>, Error> {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        self.send(ControlEvent::ListImages { images: tx })?;
+        let count = self
+            .tasks
+            .iter()
+            .filter(|task| task.kind == ResourceKind::Container)
+            .count();
+        let mut images = Vec::with_capacity(count);
+        while images.len() < count {
+            let image = timeout(REPLY_TIMEOUT, rx.recv())
+                .await?
+                .ok_or_else(|| anyhow!("The runners are closed"))?;
+            images.push(image);
+        }
+        images.sort();
+        images.dedup();
+        Ok(images)
+    }
+
+    /// Saves the images of the containers to the directory with a manifest to check them on import.
+    pub async fn export_images(&self, dir: &Path) -> Result<(), Error> {
+        let images = self.images().await?;
+        let manifest = archive::export_images(&self.driver, &images, dir).await?;
+        log::info!("{} images exported to {}", manifest.images.len(), dir.display());
+        Ok(())
+    }
+
+    /// Loads the images exported by `export_images` from the directory.
+    pub async fn import_images(&self, dir: &Path) -> Result<(), Error> {
+        let manifest = archive::import_images(&self.driver, dir).await?;
+        log::info!("{} images imported from {}", manifest.images.len(), dir.display());
+        Ok(())
+    }
+
     pub async fn recv(&mut self) -> Option<ReportEnvelope<C>> {
         self.reporter.recv().await
     }
 
-    pub fn stop(&self) {}
+    /// Stops all the tasks in the reverse order of dependencies, removes the resources that are not permanent and
+    /// terminates the runners. Does nothing if the scope is already shut down.
+    pub async fn shutdown(&mut self) -> Result<(), Error> {
+        if self.tasks.is_empty() {
+            return Ok(());
+        }
+        for index in self.shutdown_order() {
+            let task = &self.tasks[index];
+            if !task.permanent {
+                self.stop_task(task).await?;
+            }
+        }
+        self.terminate().await
+    }
+
+    /// Terminates the runners, but leaves the containers running. The next run adopts the containers with the same
+    /// spec. Does nothing if the scope is already shut down.
+    pub async fn detach(&mut self) -> Result<(), Error> {
+        if self.tasks.is_empty() {
+            return Ok(());
+        }
+        self.terminate().await
+    }
+
+    async fn terminate(&mut self) -> Result<(), Error> {
+        self.send(ControlEvent::Terminate)?;
+        for mut task in self.tasks.drain(..) {
+            if timeout(TERMINATE_TIMEOUT, &mut task.handle).await.is_err() {
+                log::warn!("Task {} is not terminated in time. Aborting it.", task.task_id);
+                task.handle.abort();
+            }
+        }
+        log::info!("Scope {} is shut down", self.scope);
+        Ok(())
+    }
+
+    /// Dependants go first: a task is stopped when no other task that remains running depends on it.
+    fn shutdown_order(&self) -> Vec<usize> {
+        let mut remaining: Vec<usize> = (0..self.tasks.len()).collect();
+        let mut order = Vec::with_capacity(remaining.len());
+        while !remaining.is_empty() {
+            let position = remaining
+                .iter()
+                .position(|&index| {
+                    let task_id = &self.tasks[index].task_id;
+                    !remaining
+                        .iter()
+                        .any(|&other| self.tasks[other].deps.contains(task_id))
+                })
+                // Dependencies have a cycle, take any
+                .unwrap_or_default();
+            order.push(remaining.remove(position));
+        }
+        order
+    }
+
+    async fn stop_task(&self, task: &TaskHandle) -> Result<(), Error> {
+        log::debug!("Stopping task {}", task.task_id);
+        let mut stopped = task.stopped.clone();
+        self.send(ControlEvent::Stop {
+            task_id: task.task_id.clone(),
+        })?;
+        let period = task.stop_timeout + STOP_TIMEOUT;
+        if wait_stopped(&mut stopped, period).await {
+            return Ok(());
+        }
+        log::warn!(
+            "Task {} is not stopped in {:?}. Removing the {} {} forcibly.",
+            task.task_id,
+            period,
+            task.kind,
+            task.name
+        );
+        let res = match task.kind {
+            // The forced removal kills a running container
+            ResourceKind::Container => self.driver.remove_container(&task.name).await,
+            ResourceKind::Network => self.driver.remove_network(&task.name).await,
+            ResourceKind::Volume => self.driver.remove_volume(&task.name).await,
+        };
+        if let Err(err) = res {
+            log::error!("Can't remove the {} {}: {}", task.kind, task.name, err);
+        }
+        if !wait_stopped(&mut stopped, FORCED_STOP_TIMEOUT).await {
+            log::error!("Task {} can't be stopped", task.task_id);
+        }
+        Ok(())
+    }
+}
+
+async fn wait_stopped(stopped: &mut watch::Receiver<bool>, period: Duration) -> bool {
+    let res = timeout(period, stopped.wait_for(|stopped| *stopped)).await;
+    matches!(res, Ok(Ok(_)))
 }
@@ -0,0 +1,97 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! The last state of the requests of a scope. The requests channel drops the oldest requests for the runners that
+//! lag behind, so they restore the config, the dependencies and the stop requests from the snapshot.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use tari_launchpad_protocol::container::TaskId;
+
+use crate::{config::ManagedProtocol, scope::ControlEvent};
+
+struct State<C: ManagedProtocol> {
+    /// Is `None` until the first config is set.
+    config: Option<Option<Arc<C::Config>>>,
+    /// The names of the resources that are ready to use.
+    ready: HashMap<TaskId, String>,
+    stopped: HashSet<TaskId>,
+    terminated: bool,
+}
+
+pub(crate) struct ScopeSnapshot<C: ManagedProtocol> {
+    state: Arc<Mutex<State<C>>>,
+}
+
+impl<C: ManagedProtocol> Clone for ScopeSnapshot<C> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<C: ManagedProtocol> Default for ScopeSnapshot<C> {
+    fn default() -> Self {
+        let state = State {
+            config: None,
+            ready: HashMap::new(),
+            stopped: HashSet::new(),
+            terminated: false,
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+}
+
+impl<C: ManagedProtocol> ScopeSnapshot<C> {
+    /// Keeps the effect of the request before it's broadcasted.
+    pub fn record(&self, event: &ControlEvent<C>) {
+        let mut state = self.lock();
+        match event {
+            ControlEvent::SetConfig(config) => {
+                state.config = Some(config.clone());
+            },
+            ControlEvent::ResourceReady { task_id, name } => {
+                state.ready.insert(task_id.clone(), name.clone());
+            },
+            ControlEvent::ResourceClosed { task_id } => {
+                state.ready.remove(task_id);
+            },
+            ControlEvent::Stop { task_id } => {
+                state.stopped.insert(task_id.clone());
+            },
+            ControlEvent::Terminate => {
+                state.terminated = true;
+            },
+            // The other requests are not repeated
+            _ => {},
+        }
+    }
+
+    /// The last config, if any config was set.
+    pub fn config(&self) -> Option<Option<Arc<C::Config>>> {
+        self.lock().config.clone()
+    }
+
+    /// The name of the resource of the task if it's ready to use.
+    pub fn ready(&self, task_id: &TaskId) -> Option<String> {
+        self.lock().ready.get(task_id).cloned()
+    }
+
+    pub fn is_stopped(&self, task_id: &TaskId) -> bool {
+        self.lock().stopped.contains(task_id)
+    }
+
+    pub fn is_terminated(&self) -> bool {
+        self.lock().terminated
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<C>> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
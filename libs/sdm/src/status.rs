@@ -38,6 +38,8 @@ pub struct SdmStatus<S> {
     status: S,
     has_work: bool,
     fallback: Option<Fallback<S>>,
+    /// The time when the status has to be checked again even without events.
+    wakeup: Option<Instant>,
 }
 
 impl<S: Default> SdmStatus<S> {
@@ -47,6 +49,7 @@ impl<S: Default> SdmStatus<S> {
             status: S::default(),
             has_work: false,
             fallback: None,
+            wakeup: None,
         }
     }
 }
@@ -63,13 +66,32 @@ impl<S> SdmStatus<S> {
     pub fn reset_has_work_flag(&mut self) {
         self.has_work = false;
     }
+
+    /// Asks to update the task at `when`. The earliest time wins if it's called several times during the update.
+    pub fn wake_at(&mut self, when: Instant) {
+        let when = self.wakeup.map_or(when, |wakeup| wakeup.min(when));
+        self.wakeup = Some(when);
+    }
+
+    pub fn reset_wakeup(&mut self) {
+        self.wakeup = None;
+    }
+
+    /// The time of the next update that is not caused by an event, if any.
+    pub fn next_wakeup(&self) -> Option<Instant> {
+        let fallback = self.fallback.as_ref().map(|fallback| fallback.when);
+        match (self.wakeup, fallback) {
+            (Some(wakeup), Some(fallback)) => Some(wakeup.min(fallback)),
+            (wakeup, fallback) => wakeup.or(fallback),
+        }
+    }
 }
 
 impl<S: TaskStatusChecker> SdmStatus<S> {
     pub fn check_fallback(&mut self) {
         if let Some(fallback) = self.fallback.as_ref() {
-            let now = Instant::now();
-            if fallback.when < now {
+            // The runner sleeps until exactly that time
+            if fallback.when <= Instant::now() {
                 let fallback = self.fallback.take().unwrap();
                 self.set(fallback.next_status);
             }
@@ -90,7 +112,8 @@ impl<S: TaskStatusChecker> SdmStatus<S> {
         func(&mut self.status);
     }
 
-    // pub fn set_fallback(&mut self, fallback: Fallback<S>) {
-    // self.fallback = Some(fallback);
-    // }
+    /// Sets the `next_status` when the time comes. Any other status set before cancels it.
+    pub fn set_fallback(&mut self, fallback: Fallback<S>) {
+        self.fallback = Some(fallback);
+    }
 }
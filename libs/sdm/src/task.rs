@@ -21,32 +21,42 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::{collections::HashMap, fmt};
+use std::{collections::HashMap, fmt, sync::Arc};
 
 use anyhow::Error;
 use async_trait::async_trait;
-use bollard::Docker;
 use chrono::Local;
 use derive_more::{Deref, DerefMut};
 use futures::StreamExt;
 use log::*;
 use tari_launchpad_protocol::{
-    container::{LogLevel, LogRecord, StatsData, TaskDelta, TaskId, TaskState, TaskStatus as TaskStatusValue},
+    container::{
+        DaemonState, LogLevel, LogRecord, RestartRecord, StatsData, TaskDelta, TaskId, TaskProgress, TaskState,
+        TaskStatus as TaskStatusValue,
+    },
     errors::ErrorRecord,
 };
 use tokio::{
     select,
-    sync::{broadcast, mpsc},
-    time::{sleep, Duration, Instant},
+    sync::{broadcast, mpsc, watch, Semaphore},
+    time::{sleep_until, Duration, Instant},
 };
 use tokio_stream::wrappers::{BroadcastStream, UnboundedReceiverStream};
 
 use crate::{
     config::ManagedProtocol,
+    driver::{ContainerDriver, DriverStream, ResourceEvent, ResourceKind},
+    hub::EventsHub,
     scope::{ControlEvent, Report, ReportEnvelope},
+    snapshot::ScopeSnapshot,
     status::SdmStatus,
+    utils::backoff,
 };
 
+/// The delay before the update is retried after an error. It's doubled for every next failure.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
 pub trait ManagedTask {
     fn id() -> TaskId;
 
@@ -59,6 +69,11 @@ pub trait TaskStatusChecker: fmt::Debug + Default + Send {
     fn is_ready(&self) -> bool {
         false
     }
+
+    /// The task doesn't hold any resources in the runtime and can be terminated.
+    fn is_idle(&self) -> bool {
+        false
+    }
 }
 
 #[async_trait]
@@ -71,6 +86,11 @@ pub trait RunnableTask: Sized + Send + 'static {
 
     /// Indicates whether any resources created by this task should persist between runs.
     fn is_permanent(&self) -> bool;
+
+    /// How long the resources of the task are allowed to be released gracefully.
+    fn stop_timeout(&self) -> Duration {
+        Duration::ZERO
+    }
 }
 
 #[async_trait]
@@ -79,8 +99,18 @@ pub trait RunnableContext<T: RunnableTask> {
     async fn initialize(&mut self);
     fn reconfigure(&mut self, config: Option<&<T::Protocol as ManagedProtocol>::Config>) -> bool;
     fn process_inner_event(&mut self, event: <T::Protocol as ManagedProtocol>::Inner);
+    /// Pulls the image again and recreates the resource. Only containers have images.
+    fn update_image(&mut self) {}
+    /// Pulls the image in the background when a permit is acquired. Only containers have images.
+    fn pull_image(&mut self, _permits: Arc<Semaphore>) {}
+    /// The image of the resource with the current config. Only containers have images.
+    fn image_name(&self) -> Option<&str> {
+        None
+    }
     fn process_event(&mut self, event: T::Event) -> Result<(), Error>;
     async fn update(&mut self) -> Result<(), Error>;
+    /// Checks the resource again, because the events could be lost while the daemon was unavailable.
+    fn reconcile(&mut self);
 }
 
 pub struct TaskSender<E, P: ManagedProtocol> {
@@ -102,6 +132,10 @@ impl<E, P: ManagedProtocol> Clone for TaskSender<E, P> {
 }
 
 impl<E, P: ManagedProtocol> TaskSender<E, P> {
+    pub fn task_id(&self) -> &TaskId {
+        &self.task_id
+    }
+
     pub fn get_direct(&self) -> &mpsc::UnboundedSender<E> {
         &self.event_tx
     }
@@ -164,28 +198,77 @@ impl<E, P: ManagedProtocol> TaskSender<E, P> {
         let report = Report::Delta(delta);
         self.send_report(report)
     }
+
+    pub fn send_update_available(&self, available: bool) -> Result<(), Error> {
+        let delta = TaskDelta::UpdateAvailable(available);
+        let report = Report::Delta(delta);
+        self.send_report(report)
+    }
+
+    pub fn send_pull_progress(&self, progress: TaskProgress) -> Result<(), Error> {
+        let delta = TaskDelta::PullProgress(progress);
+        let report = Report::Delta(delta);
+        self.send_report(report)
+    }
+
+    pub fn send_spec_changed(&self, fields: &[&str]) -> Result<(), Error> {
+        let fields = fields.iter().map(ToString::to_string).collect();
+        let delta = TaskDelta::SpecChanged(fields);
+        let report = Report::Delta(delta);
+        self.send_report(report)
+    }
+
+    pub fn send_restart(&self, attempt: u32, delay: Duration, exit_code: Option<i64>) -> Result<(), Error> {
+        let record = RestartRecord {
+            datetime: Local::now().naive_local(),
+            attempt,
+            delay: delay.as_secs(),
+            exit_code,
+        };
+        let delta = TaskDelta::Restart(record);
+        let report = Report::Delta(delta);
+        self.send_report(report)
+    }
 }
 
 #[derive(Deref, DerefMut)]
-pub struct TaskContext<T: RunnableTask> {
+pub struct TaskContext<T: RunnableTask, D: ContainerDriver> {
     /// Filled by a dependencies controller
     dependencies_ready: bool,
     resources_map: HashMap<TaskId, String>,
     /// Depends on the config
     should_start: bool,
+    /// The config was received at least once
+    configured: bool,
     pub status: SdmStatus<T::Status>,
     sender: TaskSender<T::Event, T::Protocol>,
-    pub driver: Docker,
+    pub driver: D,
+    hub: EventsHub,
     #[deref]
     #[deref_mut]
     pub inner: T,
 }
 
-impl<T: RunnableTask> TaskContext<T> {
+impl<T: RunnableTask, D: ContainerDriver> TaskContext<T, D> {
     pub fn should_be_active(&self) -> bool {
         self.should_start && self.dependencies_ready
     }
 
+    pub fn is_configured(&self) -> bool {
+        self.configured
+    }
+
+    /// The events of the resource from the stream shared by the scope.
+    pub(crate) fn resource_events(&self, kind: ResourceKind, name: &str) -> DriverStream<ResourceEvent> {
+        self.hub.subscribe(kind, name)
+    }
+
+    /// It's not known yet whether the task will be active: the config is not received, or the dependencies of the
+    /// task that has to start are not ready.
+    pub fn activation_pending(&self) -> bool {
+        !self.configured || (self.should_start && !self.dependencies_ready)
+    }
+
     pub fn resource(&self, id: &TaskId) -> Option<&str> {
         self.resources_map.get(id).map(String::as_ref)
     }
@@ -201,27 +284,39 @@ impl<T: RunnableTask> TaskContext<T> {
     }
 }
 
-pub struct SdmTaskRunner<R: RunnableTask> {
+pub struct SdmTaskRunner<R: RunnableTask, D: ContainerDriver> {
     task_id: TaskId,
     events_receiver: Option<mpsc::UnboundedReceiver<R::Event>>,
     requests_receiver: Option<broadcast::Receiver<ControlEvent<R::Protocol>>>,
     requests_sender: broadcast::Sender<ControlEvent<R::Protocol>>,
-    context: TaskContext<R>,
-    next_update: Instant,
+    /// Restores the missed requests.
+    snapshot: ScopeSnapshot<R::Protocol>,
+    context: TaskContext<R, D>,
+    /// The update failed and is postponed till that time.
+    retry_at: Option<Instant>,
+    /// The number of the failed updates in a row.
+    failures: u32,
     /// Waits when these dependencies started.
     dependencies: HashMap<TaskId, bool>,
     ready_to_use: bool,
+    /// The scope asked to stop the task. The configuration is ignored after that.
+    stop_requested: bool,
+    /// Is set when the task is stopped after the request.
+    stopped: watch::Sender<bool>,
+    terminated: bool,
 }
 
-impl<R: RunnableTask> SdmTaskRunner<R>
+impl<R: RunnableTask, D: ContainerDriver> SdmTaskRunner<R, D>
 where
-    TaskContext<R>: RunnableContext<R>,
+    TaskContext<R, D>: RunnableContext<R>,
 {
     pub fn new<M: ManagedTask>(
         req_tx: broadcast::Sender<ControlEvent<R::Protocol>>,
         rep_tx: mpsc::UnboundedSender<ReportEnvelope<R::Protocol>>,
         inner: R,
-        docker: Docker,
+        driver: D,
+        hub: EventsHub,
+        snapshot: ScopeSnapshot<R::Protocol>,
     ) -> Self {
         let task_id = M::id();
         let (event_tx, event_rx) = mpsc::unbounded_channel();
@@ -235,9 +330,11 @@ where
             dependencies_ready: false,
             resources_map: HashMap::new(),
             should_start: false,
+            configured: false,
             status: SdmStatus::new(inner.name().to_string()),
             sender,
-            driver: docker,
+            driver,
+            hub,
             inner,
         };
         // It subscribed here to avoid the gap if that will subscribe in the routine.
@@ -249,10 +346,15 @@ where
             events_receiver: Some(event_rx),
             requests_receiver: Some(req_rx),
             requests_sender: req_tx,
+            snapshot,
             context,
-            next_update: Instant::now(),
+            retry_at: None,
+            failures: 0,
             dependencies,
             ready_to_use: false,
+            stop_requested: false,
+            stopped: watch::channel(false).0,
+            terminated: false,
         }
     }
 
@@ -265,15 +367,20 @@ where
     pub async fn routine(&mut self) -> Result<(), Error> {
         self.check_dependencies();
         self.initialize().await?;
-        let interval = Duration::from_millis(1_000);
         let events_receiver = self.events_receiver.take().unwrap();
         let mut events = UnboundedReceiverStream::new(events_receiver);
         let requests_receiver = self.requests_receiver.take().unwrap();
         let mut requests = BroadcastStream::new(requests_receiver);
+        let mut daemon = self.context.hub.daemon_state();
         loop {
+            self.update().await;
+            self.notify_dependants();
+            self.notify_stopped();
+            // The task sleeps until an event, a request or a timer of the status
+            let wakeup = self.next_wakeup();
             select! {
-                _ = sleep(interval) => {
-                    // log::trace!("Checking the scope by interval");
+                _ = sleep_until(wakeup.unwrap_or_else(Instant::now)), if wakeup.is_some() => {
+                    log::trace!("Task {} is woken up by the timer", self.task_id);
                 }
                 event = events.next() => {
                     if let Some(event) = event {
@@ -283,21 +390,63 @@ where
                         break;
                     }
                 }
-                req = requests.next() => {
-                    if let Some(Ok(req)) = req {
-                        self.process_request(req);
-                    } else {
-                        log::info!("Requests stream closed");
+                res = daemon.changed() => {
+                    if res.is_err() {
+                        log::info!("Daemon state closed");
                         break;
                     }
+                    let state = *daemon.borrow_and_update();
+                    // It's changed to available only after the connection was lost
+                    if state == DaemonState::Available {
+                        log::info!("The daemon is available again. Reconciling the task {}", self.task_id);
+                        self.retry_at = None;
+                        self.context.reconcile();
+                    }
+                }
+                req = requests.next() => {
+                    match req {
+                        Some(Ok(req)) => {
+                            self.process_request(req);
+                        },
+                        Some(Err(err)) => {
+                            // The task was too slow, the next requests are delivered, but the missed ones are lost
+                            log::warn!("Task {} missed the requests: {}", self.task_id, err);
+                            self.resync();
+                        },
+                        None => {
+                            log::info!("Requests stream closed");
+                            break;
+                        },
+                    }
                 }
             }
-            self.update().await;
-            self.notify_dependants();
+            if self.terminated {
+                log::debug!("Task {} is terminated", self.task_id);
+                break;
+            }
         }
         Ok(())
     }
 
+    fn next_wakeup(&self) -> Option<Instant> {
+        // The other timers don't matter until the retry
+        self.retry_at.or_else(|| self.context.status.next_wakeup())
+    }
+
+    /// Subscribes to the flag that is set when the task is stopped by the `ControlEvent::Stop` request.
+    pub fn subscribe_stopped(&self) -> watch::Receiver<bool> {
+        self.stopped.subscribe()
+    }
+
+    fn notify_stopped(&mut self) {
+        let stopped = self.stop_requested && self.context.status.is_idle();
+        self.stopped.send_if_modified(|value| {
+            let changed = *value != stopped;
+            *value = stopped;
+            changed
+        });
+    }
+
     fn notify_dependants(&mut self) {
         if self.context.status.is_ready() {
             // Notifies dependants about the entity is ready to use
@@ -320,6 +469,7 @@ where
     }
 
     fn broadcast(&mut self, event: ControlEvent<R::Protocol>) {
+        self.snapshot.record(&event);
         if let Err(err) = self.requests_sender.send(event) {
             log::error!("Can't broadcast event: {:?}", err);
         }
@@ -350,9 +500,68 @@ where
             ControlEvent::InnerEvent(inner) => {
                 self.process_inner_event(inner);
             },
+            ControlEvent::Stop { task_id } => {
+                if task_id == self.task_id {
+                    debug!("[SdmTaskRunner::process_request] Task {} is stopping", self.task_id);
+                    self.stop_requested = true;
+                    self.context.should_start = false;
+                    self.retry_at = None;
+                }
+            },
+            ControlEvent::UpdateImage { task_id } => {
+                if task_id == self.task_id {
+                    self.context.update_image();
+                }
+            },
+            ControlEvent::PullImages { tasks, permits } => {
+                let requested = match tasks {
+                    Some(tasks) => tasks.contains(&self.task_id),
+                    None => true,
+                };
+                if requested {
+                    self.context.pull_image(permits);
+                }
+            },
+            ControlEvent::ListImages { images } => {
+                if let Some(image) = self.context.image_name() {
+                    images.send(image.to_string()).ok();
+                }
+            },
+            ControlEvent::Terminate => {
+                self.terminated = true;
+            },
         }
     }
 
+    /// Restores the config, the dependencies and the stop request from the snapshot of the scope after the missed
+    /// requests, and checks the resource again.
+    fn resync(&mut self) {
+        if self.snapshot.is_terminated() {
+            self.terminated = true;
+            return;
+        }
+        if self.snapshot.is_stopped(&self.task_id) {
+            let task_id = self.task_id.clone();
+            self.process_request(ControlEvent::Stop { task_id });
+        } else if let Some(config) = self.snapshot.config() {
+            self.reconfigure(config.as_deref());
+        }
+        for (task_id, ready) in self.dependencies.iter_mut() {
+            match self.snapshot.ready(task_id) {
+                Some(name) => {
+                    *ready = true;
+                    self.context.resources_map.insert(task_id.clone(), name);
+                },
+                None => {
+                    *ready = false;
+                    self.context.resources_map.remove(task_id);
+                },
+            }
+        }
+        self.check_dependencies();
+        self.context.reconcile();
+    }
+
     /// Checks whether all dependencies have a ready status and sets the `dependencies_ready` field in the context
     /// accordingly.. Does not trigger any actions.
     fn check_dependencies(&mut self) {
@@ -370,6 +579,13 @@ where
     }
 
     pub fn reconfigure(&mut self, config: Option<&<R::Protocol as ManagedProtocol>::Config>) {
+        if self.stop_requested {
+            debug!(
+                "[SdmTaskRunner::reconfigure] Task {} is stopped and ignores the config",
+                self.task_id
+            );
+            return;
+        }
         let active = self.context.reconfigure(config);
         if active {
             debug!("[SdmTaskRunner::reconfigure] Task {} is queued to start", self.task_id)
@@ -377,6 +593,9 @@ where
             debug!("[SdmTaskRunner::reconfigure] Task {} is will NOT start", self.task_id)
         }
         self.context.should_start = active;
+        self.context.configured = true;
+        // The new config could fix the error
+        self.retry_at = None;
     }
 
     pub fn process_inner_event(&mut self, event: <R::Protocol as ManagedProtocol>::Inner) {
@@ -396,19 +615,24 @@ where
             self.context.name(),
             self.context.status.get()
         );
-        loop {
-            let now = Instant::now();
-            if self.next_update > now {
-                continue;
+        if let Some(retry_at) = self.retry_at {
+            if retry_at > Instant::now() {
+                return;
             }
+            self.retry_at = None;
+        }
+        loop {
             self.context.status.check_fallback();
             self.context.status.reset_has_work_flag();
+            self.context.status.reset_wakeup();
             if let Err(err) = self.context.update().await {
-                error!("Update error: {}", err);
-                self.next_update = now + Duration::from_secs(5);
-                // TODO: Set a fallback? and reset it when succeed?
+                self.failures += 1;
+                let delay = backoff(self.failures, RETRY_DELAY, MAX_RETRY_DELAY);
+                error!("Update error (retry in {:?}): {}", delay, err);
+                self.retry_at = Some(Instant::now() + delay);
                 break;
             }
+            self.failures = 0;
             if !self.context.status.has_work() {
                 break;
             }
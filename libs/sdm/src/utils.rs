@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use rand::distributions::{Alphanumeric, Distribution};
 use tokio::task::JoinHandle;
@@ -52,6 +52,12 @@ impl<T> Drop for TaskGuardInner<T> {
 }
 
 /// Create a cryptographically secure password on length `len`
+/// The exponential backoff: the `base` delay is doubled for every `attempt` after the first one up to the `max`.
+pub fn backoff(attempt: u32, base: Duration, max: Duration) -> Duration {
+    let factor = 2_u32.saturating_pow(attempt.saturating_sub(1));
+    base.saturating_mul(factor).min(max)
+}
+
 pub fn create_password(len: usize) -> String {
     let mut rng = rand::thread_rng();
     Alphanumeric.sample_iter(&mut rng).take(len).map(char::from).collect()
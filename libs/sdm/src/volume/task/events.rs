@@ -25,9 +25,9 @@ use anyhow::Error;
 use tari_launchpad_protocol::container::TaskStatus;
 
 use super::{Event, Status, VolumeTask};
-use crate::{config::ManagedProtocol, task::TaskContext};
+use crate::{config::ManagedProtocol, driver::ContainerDriver, task::TaskContext};
 
-impl<C: ManagedProtocol> TaskContext<VolumeTask<C>> {
+impl<C: ManagedProtocol, D: ContainerDriver> TaskContext<VolumeTask<C>, D> {
     pub fn process_event_impl(&mut self, event: Event) -> Result<(), Error> {
         match event {
             Event::Created => self.on_created(),
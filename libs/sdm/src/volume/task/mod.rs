@@ -31,6 +31,7 @@ use async_trait::async_trait;
 use super::ManagedVolume;
 use crate::{
     config::ManagedProtocol,
+    driver::ContainerDriver,
     error::ParseError,
     task::{RunnableContext, RunnableTask, TaskContext, TaskEvent, TaskStatusChecker},
     utils::TaskGuard,
@@ -70,7 +71,7 @@ impl<C: ManagedProtocol> RunnableTask for VolumeTask<C> {
 }
 
 #[async_trait]
-impl<C: ManagedProtocol> RunnableContext<VolumeTask<C>> for TaskContext<VolumeTask<C>> {
+impl<C: ManagedProtocol, D: ContainerDriver> RunnableContext<VolumeTask<C>> for TaskContext<VolumeTask<C>, D> {
     async fn initialize(&mut self) {
         self.subscribe_to_events();
     }
@@ -90,6 +91,12 @@ impl<C: ManagedProtocol> RunnableContext<VolumeTask<C>> for TaskContext<VolumeTa
     async fn update(&mut self) -> Result<(), Error> {
         self.process_update_impl().await
     }
+
+    fn reconcile(&mut self) {
+        if !matches!(self.status.get(), Status::InitialState) {
+            self.status.set(Status::Checking);
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -25,9 +25,9 @@ use anyhow::Error;
 use tari_launchpad_protocol::container::TaskStatus;
 
 use super::{Status, VolumeTask};
-use crate::{config::ManagedProtocol, task::TaskContext};
+use crate::{config::ManagedProtocol, driver::ContainerDriver, task::TaskContext};
 
-impl<C: ManagedProtocol> TaskContext<VolumeTask<C>> {
+impl<C: ManagedProtocol, D: ContainerDriver> TaskContext<VolumeTask<C>, D> {
     pub async fn process_update_impl(&mut self) -> Result<(), Error> {
         match self.status.get() {
             Status::InitialState => self.do_initial_state().await,
@@ -0,0 +1,863 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{collections::HashMap, env, fs, mem, time::Duration};
+
+use anyhow::Error;
+use futures::StreamExt;
+use tari_launchpad_protocol::container::{DaemonState, TaskDelta, TaskStatus};
+use tari_sdm::{
+    archive::MANIFEST_FILE,
+    config::ManagedProtocol,
+    driver::{
+        ContainerDriver, ContainerSpec, FakeDriver, HealthState, PortExposure, PortSpec, ResourceKind, ResourceLimits,
+    },
+    ids::{ManagedTask, TaskId},
+    image::{Envs, Limits, ManagedContainer, Networks, Ports, RestartPolicy},
+    network::ManagedNetwork,
+    volume::ManagedVolume,
+    Report, SdmScope,
+};
+use tokio::{
+    sync::watch,
+    time::{sleep, timeout},
+};
+
+const SCOPE: &str = "test";
+const IMAGE: &str = "registry/app:latest";
+const APP_PORT: u16 = 8080;
+const GATEWAY_PORT: u16 = 9090;
+
+#[derive(Debug)]
+struct TestProtocol;
+
+#[derive(Debug, Default)]
+struct TestConfig {
+    tag: Option<String>,
+    memory: Option<u64>,
+    port: Option<u16>,
+    mode: Option<String>,
+    gateway_port: Option<u16>,
+}
+
+impl ManagedProtocol for TestProtocol {
+    type Config = TestConfig;
+    type Inner = ();
+    type Outer = ();
+}
+
+#[derive(Debug)]
+struct TestNet;
+
+impl ManagedTask for TestNet {
+    fn id() -> TaskId {
+        "TestNet".into()
+    }
+}
+
+impl ManagedNetwork for TestNet {
+    type Protocol = TestProtocol;
+
+    fn network_name(&self) -> &str {
+        "network"
+    }
+}
+
+#[derive(Debug)]
+struct TestVolume;
+
+impl ManagedTask for TestVolume {
+    fn id() -> TaskId {
+        "TestVolume".into()
+    }
+}
+
+impl ManagedVolume for TestVolume {
+    type Protocol = TestProtocol;
+
+    fn volume_name(&self) -> &str {
+        "volume"
+    }
+}
+
+#[derive(Debug, Default)]
+struct TestApp {
+    tag: Option<String>,
+    memory: Option<u64>,
+    port: Option<u16>,
+    mode: Option<String>,
+    config_changed: bool,
+}
+
+impl ManagedTask for TestApp {
+    fn id() -> TaskId {
+        "TestApp".into()
+    }
+
+    fn deps() -> Vec<TaskId> {
+        vec![TestNet::id()]
+    }
+}
+
+impl ManagedContainer for TestApp {
+    type Protocol = TestProtocol;
+
+    fn registry(&self) -> &str {
+        "registry"
+    }
+
+    fn image_name(&self) -> &str {
+        "app"
+    }
+
+    fn tag(&self) -> &str {
+        self.tag.as_deref().unwrap_or("latest")
+    }
+
+    fn reconfigure(&mut self, config: Option<&TestConfig>) -> Option<bool> {
+        self.tag = config?.tag.clone();
+        if self.memory != config?.memory || self.port != config?.port {
+            self.memory = config?.memory;
+            self.port = config?.port;
+            self.config_changed = true;
+        }
+        // The changes of the spec are detected by the task
+        self.mode = config?.mode.clone();
+        Some(true)
+    }
+
+    fn restart_required(&mut self) -> bool {
+        mem::take(&mut self.config_changed)
+    }
+
+    fn stop_timeout(&self) -> Duration {
+        Duration::from_secs(1)
+    }
+
+    fn restart_policy(&self) -> RestartPolicy {
+        RestartPolicy::OnFailure { max_retries: 1 }
+    }
+
+    fn envs(&self, envs: &mut Envs) {
+        if let Some(mode) = self.mode.as_ref() {
+            envs.set("MODE", mode);
+        }
+    }
+
+    fn ports(&self, ports: &mut Ports) {
+        ports.forward(self.port.unwrap_or(APP_PORT), APP_PORT);
+    }
+
+    fn networks(&self, networks: &mut Networks) {
+        networks.add("app", TestNet::id());
+    }
+
+    fn limits(&self, limits: &mut Limits) {
+        if let Some(memory) = self.memory {
+            limits.memory(memory);
+            limits.cpus(0.5);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TestWorker;
+
+impl ManagedTask for TestWorker {
+    fn id() -> TaskId {
+        "TestWorker".into()
+    }
+
+    fn deps() -> Vec<TaskId> {
+        vec![TestApp::id()]
+    }
+}
+
+impl ManagedContainer for TestWorker {
+    type Protocol = TestProtocol;
+
+    fn registry(&self) -> &str {
+        "registry"
+    }
+
+    fn image_name(&self) -> &str {
+        "worker"
+    }
+}
+
+/// Relies on the spec to detect the changes of the config.
+#[derive(Debug, Default)]
+struct TestGateway {
+    port: Option<u16>,
+}
+
+impl ManagedTask for TestGateway {
+    fn id() -> TaskId {
+        "TestGateway".into()
+    }
+
+    fn deps() -> Vec<TaskId> {
+        vec![TestNet::id()]
+    }
+}
+
+impl ManagedContainer for TestGateway {
+    type Protocol = TestProtocol;
+
+    fn registry(&self) -> &str {
+        "registry"
+    }
+
+    fn image_name(&self) -> &str {
+        "gateway"
+    }
+
+    fn reconfigure(&mut self, config: Option<&TestConfig>) -> Option<bool> {
+        self.port = config?.gateway_port;
+        Some(true)
+    }
+
+    fn ports(&self, ports: &mut Ports) {
+        ports.forward(self.port.unwrap_or(GATEWAY_PORT), GATEWAY_PORT);
+    }
+}
+
+struct TestScope {
+    scope: SdmScope<TestProtocol, FakeDriver>,
+    statuses: HashMap<TaskId, TaskStatus>,
+    updates: HashMap<TaskId, bool>,
+    pulls: HashMap<TaskId, u8>,
+    changes: HashMap<TaskId, Vec<String>>,
+}
+
+impl TestScope {
+    fn start(driver: &FakeDriver) -> Result<Self, Error> {
+        Self::with_scope(driver, SCOPE)
+    }
+
+    fn with_scope(driver: &FakeDriver, scope: &str) -> Result<Self, Error> {
+        let scope = SdmScope::with_driver(scope, driver.clone());
+        Self::with_resources(scope)
+    }
+
+    /// Uses the fake driver as the registry.
+    fn with_updates(driver: &FakeDriver, interval: Duration) -> Result<Self, Error> {
+        let mut scope = SdmScope::with_driver(SCOPE, driver.clone());
+        scope.check_updates(driver.clone(), interval);
+        Self::with_resources(scope)
+    }
+
+    fn with_resources(mut scope: SdmScope<TestProtocol, FakeDriver>) -> Result<Self, Error> {
+        scope.add_network(TestNet)?;
+        scope.add_volume(TestVolume)?;
+        scope.add_image(TestApp::default())?;
+        Ok(Self {
+            scope,
+            statuses: HashMap::new(),
+            updates: HashMap::new(),
+            pulls: HashMap::new(),
+            changes: HashMap::new(),
+        })
+    }
+
+    /// Reads reports until the status of the task matches the predicate.
+    async fn wait_for<F>(&mut self, id: TaskId, func: F) -> Result<(), Error>
+    where
+        F: Fn(&TaskStatus) -> bool,
+    {
+        self.wait_until(|scope| scope.statuses.get(&id).map(&func).unwrap_or_default())
+            .await
+    }
+
+    /// Reads reports until the state of the scope matches the predicate.
+    async fn wait_until<F>(&mut self, func: F) -> Result<(), Error>
+    where
+        F: Fn(&Self) -> bool,
+    {
+        while !func(self) {
+            let envelope = timeout(Duration::from_secs(10), self.scope.recv())
+                .await?
+                .ok_or_else(|| Error::msg("The scope is closed"))?;
+            match envelope.details {
+                Report::State(state) => {
+                    self.statuses.insert(envelope.task_id, state.status);
+                },
+                Report::Delta(TaskDelta::UpdateStatus(status)) => {
+                    self.statuses.insert(envelope.task_id, status);
+                },
+                Report::Delta(TaskDelta::UpdateAvailable(value)) => {
+                    self.updates.insert(envelope.task_id, value);
+                },
+                Report::Delta(TaskDelta::PullProgress(progress)) => {
+                    self.pulls.insert(envelope.task_id, progress.pct);
+                },
+                Report::Delta(TaskDelta::SpecChanged(fields)) => {
+                    self.changes.insert(envelope.task_id, fields);
+                },
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+
+    fn update_available(&self, id: &TaskId) -> bool {
+        self.updates.get(id).copied().unwrap_or_default()
+    }
+
+    fn is_pulled(&self, id: &TaskId) -> bool {
+        self.pulls.get(id) == Some(&100)
+    }
+}
+
+/// Waits until the scope reports the state of the connection to the daemon.
+async fn wait_daemon(daemon: &mut watch::Receiver<DaemonState>, expected: DaemonState) -> Result<(), Error> {
+    timeout(Duration::from_secs(10), async {
+        while *daemon.borrow_and_update() != expected {
+            daemon.changed().await?;
+        }
+        Ok::<_, Error>(())
+    })
+    .await?
+}
+
+/// The actions of the container in the order they happened.
+fn container_actions(driver: &FakeDriver, name: &str) -> Vec<String> {
+    driver
+        .history()
+        .into_iter()
+        .filter(|event| event.kind == ResourceKind::Container && event.name == name)
+        .map(|event| event.action)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_resources_created() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestNet::id(), TaskStatus::is_active).await?;
+    scope.wait_for(TestVolume::id(), TaskStatus::is_active).await?;
+    assert!(driver.has_network("test_network"));
+    assert!(driver.has_volume("test_volume"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_container_lifecycle() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    assert!(driver.has_image(IMAGE));
+    assert!(driver.is_running("test_app"));
+    let spec = driver
+        .container_spec("test_app")
+        .ok_or_else(|| Error::msg("No container"))?;
+    assert_eq!(spec.image, IMAGE);
+    assert_eq!(spec.networks, vec![("test_network".to_string(), "app".to_string())]);
+
+    scope.scope.set_config(None)?;
+    scope.wait_for(TestNet::id(), TaskStatus::is_inactive).await?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_inactive).await?;
+    assert!(driver.container_spec("test_app").is_none());
+    assert!(!driver.has_network("test_network"));
+    let actions = container_actions(&driver, "test_app");
+    assert!(
+        actions.contains(&"stop".to_string()),
+        "The container must be stopped gracefully"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_crashed_container_restarted() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+
+    driver.crash("test_app", 1);
+    scope.wait_for(TestApp::id(), |status| !status.is_active()).await?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    assert!(driver.is_running("test_app"));
+
+    // Retries are exhausted
+    driver.crash("test_app", 1);
+    scope.wait_for(TestApp::id(), TaskStatus::is_failed).await?;
+    assert!(!driver.is_running("test_app"));
+    let created = container_actions(&driver, "test_app")
+        .into_iter()
+        .filter(|action| action == "create")
+        .count();
+    assert_eq!(created, 2);
+
+    // The exited container is removed when the task is deactivated
+    scope.scope.set_config(None)?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_inactive).await?;
+    assert!(driver.container_spec("test_app").is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unhealthy_dependency_pauses_dependants() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.add_image(TestWorker)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestWorker::id(), TaskStatus::is_active).await?;
+
+    driver.set_health("test_app", HealthState::Unhealthy("No response".into()));
+    scope.wait_for(TestApp::id(), TaskStatus::is_unhealthy).await?;
+    scope.wait_for(TestWorker::id(), TaskStatus::is_inactive).await?;
+    assert!(driver.is_running("test_app"), "The unhealthy container is not stopped");
+    assert!(!driver.is_running("test_worker"));
+
+    driver.set_health("test_app", HealthState::Healthy);
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    scope.wait_for(TestWorker::id(), TaskStatus::is_active).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_image_changed() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+
+    let config = TestConfig {
+        tag: Some("v2".into()),
+        ..Default::default()
+    };
+    scope.scope.set_config(Some(config))?;
+    scope.wait_for(TestApp::id(), |status| !status.is_active()).await?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    assert!(driver.has_image("registry/app:v2"));
+    let spec = driver
+        .container_spec("test_app")
+        .ok_or_else(|| Error::msg("No container"))?;
+    assert_eq!(spec.image, "registry/app:v2");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stubborn_container_killed() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    driver.ignore_stop("test_app");
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+
+    scope.scope.set_config(None)?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_inactive).await?;
+    assert!(driver.container_spec("test_app").is_none());
+    // Killed only after the grace period
+    let actions = container_actions(&driver, "test_app");
+    assert_eq!(actions, vec!["create", "start", "kill", "kill", "die", "destroy"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_limits_applied() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    let config = TestConfig {
+        memory: Some(64 * 1024 * 1024),
+        ..Default::default()
+    };
+    scope.scope.set_config(Some(config))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    let spec = driver
+        .container_spec("test_app")
+        .ok_or_else(|| Error::msg("No container"))?;
+    let expected = ResourceLimits {
+        cpu_period: Some(100_000),
+        cpu_quota: Some(50_000),
+        memory: Some(64 * 1024 * 1024),
+        ..Default::default()
+    };
+    assert_eq!(spec.limits, expected);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_restarted_on_reconfigure() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+
+    let config = TestConfig {
+        memory: Some(64 * 1024 * 1024),
+        ..Default::default()
+    };
+    scope.scope.set_config(Some(config))?;
+    scope.wait_for(TestApp::id(), |status| !status.is_active()).await?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    let spec = driver
+        .container_spec("test_app")
+        .ok_or_else(|| Error::msg("No container"))?;
+    assert_eq!(spec.limits.memory, Some(64 * 1024 * 1024));
+    let created = container_actions(&driver, "test_app")
+        .into_iter()
+        .filter(|action| action == "create")
+        .count();
+    assert_eq!(created, 2, "The container must be recreated");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dangling_container_replaced() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    // A container left running by a previous run
+    driver.add_image(IMAGE);
+    let spec = ContainerSpec {
+        image: IMAGE.into(),
+        ..Default::default()
+    };
+    driver.create_container("test_app", spec).await?;
+    driver.start_container("test_app").await?;
+
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    let spec = driver
+        .container_spec("test_app")
+        .ok_or_else(|| Error::msg("No container"))?;
+    assert!(!spec.networks.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pulling_failed() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    driver.make_unavailable(IMAGE);
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_failed).await?;
+    assert!(!driver.is_running("test_app"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_shutdown() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.add_image(TestWorker)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestWorker::id(), TaskStatus::is_active).await?;
+
+    timeout(Duration::from_secs(30), scope.scope.shutdown()).await??;
+    assert!(!driver.is_running("test_app"));
+    assert!(!driver.is_running("test_worker"));
+    assert!(!driver.has_network("test_network"));
+    assert!(driver.has_volume("test_volume"), "Permanent resources must be kept");
+
+    // Dependants are stopped first
+    let destroyed: Vec<String> = driver
+        .history()
+        .into_iter()
+        .filter(|event| event.action == "destroy")
+        .map(|event| event.name)
+        .collect();
+    assert_eq!(destroyed, vec!["test_worker", "test_app", "test_network"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_scopes_isolated() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    timeout(Duration::from_secs(30), scope.scope.shutdown()).await??;
+
+    let mut other = TestScope::with_scope(&driver, "other")?;
+    other.scope.set_config(Some(TestConfig::default()))?;
+    other.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    assert!(driver.is_running("other_app"));
+    assert!(!driver.is_running("test_app"));
+    assert!(
+        driver.has_volume("test_volume"),
+        "The volume of the previous scope must be kept"
+    );
+    assert!(driver.has_volume("other_volume"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_image_updated() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    driver.publish(IMAGE, "sha256:1");
+    let mut scope = TestScope::with_updates(&driver, Duration::from_millis(100))?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+
+    driver.publish(IMAGE, "sha256:2");
+    scope.wait_until(|scope| scope.update_available(&TestApp::id())).await?;
+
+    scope.scope.update_image(TestApp::id())?;
+    scope
+        .wait_until(|scope| !scope.update_available(&TestApp::id()))
+        .await?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    assert_eq!(driver.image_digests(IMAGE).await, vec!["sha256:2"]);
+    let created = container_actions(&driver, "test_app")
+        .into_iter()
+        .filter(|action| action == "create")
+        .count();
+    assert_eq!(created, 2, "The container must be recreated");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_images_pulled_ahead() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.pull_images(None, 1)?;
+    scope.wait_until(|scope| scope.is_pulled(&TestApp::id())).await?;
+    assert!(driver.has_image(IMAGE));
+    assert!(
+        container_actions(&driver, "test_app").is_empty(),
+        "The container must not be created"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_images_transferred() -> Result<(), Error> {
+    let dir = env::temp_dir().join(format!("tari_sdm_images_{}", rand::random::<u64>()));
+    let source = FakeDriver::default();
+    source.add_image(IMAGE);
+    let scope = TestScope::start(&source)?;
+    scope.scope.export_images(&dir).await?;
+
+    // The target host has no registry access
+    let target = FakeDriver::default();
+    target.make_unavailable(IMAGE);
+    let other = TestScope::with_scope(&target, "other")?;
+    other.scope.import_images(&dir).await?;
+    assert!(target.has_image(IMAGE));
+
+    let manifest = fs::read_to_string(dir.join(MANIFEST_FILE))?;
+    fs::write(dir.join(MANIFEST_FILE), manifest.replace("sha256:", "sha256:0"))?;
+    let res = other.scope.import_images(&dir).await;
+    fs::remove_dir_all(&dir)?;
+    assert!(res.is_err(), "The changed images must be rejected");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_port_in_use() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    driver.occupy_port(GATEWAY_PORT);
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.add_image(TestGateway::default())?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestGateway::id(), TaskStatus::is_failed).await?;
+    let status = scope.statuses[&TestGateway::id()].to_string();
+    assert!(status.contains("9090"), "The busy port must be reported: {}", status);
+    assert!(container_actions(&driver, "test_gateway").is_empty());
+
+    // The container is created once another port is set
+    let config = TestConfig {
+        gateway_port: Some(19090),
+        ..Default::default()
+    };
+    scope.scope.set_config(Some(config))?;
+    scope.wait_for(TestGateway::id(), TaskStatus::is_active).await?;
+    let spec = driver
+        .container_spec("test_gateway")
+        .ok_or_else(|| Error::msg("No container"))?;
+    assert_eq!(spec.ports, vec![PortSpec {
+        container: GATEWAY_PORT,
+        host: 19090,
+        exposure: PortExposure::Loopback,
+    }]);
+    assert_eq!(
+        scope.scope.host_port(&TestGateway::id(), GATEWAY_PORT).await,
+        Some(19090)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_released_port_retried() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    driver.occupy_port(GATEWAY_PORT);
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.add_image(TestGateway::default())?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestGateway::id(), TaskStatus::is_failed).await?;
+
+    driver.release_port(GATEWAY_PORT);
+    scope.wait_for(TestGateway::id(), TaskStatus::is_active).await?;
+    assert!(driver.is_running("test_gateway"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_running_container_adopted() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    scope.scope.detach().await?;
+    assert!(driver.is_running("test_app"));
+
+    // The next run takes over the container with the same spec
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    assert_eq!(container_actions(&driver, "test_app"), vec!["create", "start"]);
+    scope.scope.detach().await?;
+
+    // The container with another spec is recreated
+    let mut scope = TestScope::start(&driver)?;
+    let config = TestConfig {
+        memory: Some(256 * 1024 * 1024),
+        ..Default::default()
+    };
+    scope.scope.set_config(Some(config))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    let created = container_actions(&driver, "test_app")
+        .into_iter()
+        .filter(|action| action == "create")
+        .count();
+    assert_eq!(created, 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_restarted_on_spec_change() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+
+    let config = TestConfig {
+        mode: Some("solo".into()),
+        ..Default::default()
+    };
+    scope.scope.set_config(Some(config))?;
+    scope
+        .wait_until(|scope| scope.changes.contains_key(&TestApp::id()))
+        .await?;
+    assert_eq!(scope.changes[&TestApp::id()], vec!["env"]);
+    scope.wait_for(TestApp::id(), |status| !status.is_active()).await?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    let spec = driver
+        .container_spec("test_app")
+        .ok_or_else(|| Error::msg("No container"))?;
+    assert!(spec.env.contains(&"MODE=solo".to_string()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failed_update_retried_with_backoff() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    driver.break_create("test_app");
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope
+        .wait_for(TestApp::id(), |status| *status == TaskStatus::Pending)
+        .await?;
+    // The retries are scheduled after 1s and 2s more, the task sleeps in between
+    sleep(Duration::from_millis(3_500)).await;
+    let attempts = driver.create_attempts("test_app");
+    assert!((2..=3).contains(&attempts), "{} attempts in 3.5s", attempts);
+    assert!(!driver.is_running("test_app"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_events_stream_shared() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.add_image(TestWorker)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestWorker::id(), TaskStatus::is_active).await?;
+    assert_eq!(driver.event_subscriptions(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_events_filtered_by_scope() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut events = driver.events("test");
+    driver.create_network("other_network").await?;
+    driver.create_network("test_network").await?;
+    let event = timeout(Duration::from_secs(5), events.next())
+        .await?
+        .expect("no events")?;
+    assert_eq!(event.name, "test_network");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_events_stream_reconnected() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+
+    driver.interrupt_events();
+    timeout(Duration::from_secs(5), async {
+        while driver.event_subscriptions() < 2 {
+            sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await?;
+    // The crash is delivered through the new stream
+    driver.crash("test_app", 1);
+    scope.wait_for(TestApp::id(), |status| !status.is_active()).await?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    assert!(driver.is_running("test_app"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reconciled_after_daemon_restart() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    let mut daemon = scope.scope.daemon_state();
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+
+    // The running container is kept
+    driver.go_offline();
+    wait_daemon(&mut daemon, DaemonState::DockerUnavailable).await?;
+    driver.go_online();
+    wait_daemon(&mut daemon, DaemonState::Available).await?;
+    sleep(Duration::from_millis(200)).await;
+    assert!(driver.is_running("test_app"));
+    assert_eq!(container_actions(&driver, "test_app"), vec!["create", "start"]);
+
+    // The exit is noticed without the event
+    driver.go_offline();
+    wait_daemon(&mut daemon, DaemonState::DockerUnavailable).await?;
+    driver.crash("test_app", 1);
+    driver.go_online();
+    wait_daemon(&mut daemon, DaemonState::Available).await?;
+    scope.wait_for(TestApp::id(), |status| !status.is_active()).await?;
+    scope.wait_for(TestApp::id(), TaskStatus::is_active).await?;
+    assert!(driver.is_running("test_app"));
+    assert_eq!(driver.event_subscriptions(), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_missed_requests_restored() -> Result<(), Error> {
+    let driver = FakeDriver::default();
+    let mut scope = TestScope::start(&driver)?;
+    scope.scope.add_image(TestWorker)?;
+    scope.scope.set_config(Some(TestConfig::default()))?;
+    // The runners don't run in between, so the config is pushed out of the requests channel
+    for _ in 0..2_000 {
+        scope.scope.update_image("Unknown".into())?;
+    }
+    scope.wait_for(TestWorker::id(), TaskStatus::is_active).await?;
+    assert!(driver.is_running("test_app"));
+    assert!(driver.is_running("test_worker"));
+    Ok(())
+}
@@ -0,0 +1,216 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{path::Path, time::Duration};
+
+use anyhow::Error;
+use log::*;
+use tari_launchpad_protocol::{
+    container::{TaskDelta, TaskProgress, TaskState},
+    launchpad::{Action, LaunchpadAction, LaunchpadDelta, LaunchpadState, Reaction},
+    settings::LaunchpadSettings,
+    wallet::WalletAction,
+};
+use tokio::{select, sync::mpsc, time::interval};
+
+use crate::{chain::SimChain, tasks, tasks::SimTask};
+
+/// How often the simulated tasks move to the next stage.
+const TICK_INTERVAL: Duration = Duration::from_millis(500);
+
+pub type BusTx = mpsc::UnboundedSender<Action>;
+pub type BusRx = mpsc::UnboundedReceiver<Reaction>;
+
+pub struct LaunchpadBus {
+    pub incoming: mpsc::UnboundedSender<Action>,
+    pub outgoing: mpsc::UnboundedReceiver<Reaction>,
+}
+
+impl LaunchpadBus {
+    pub fn start() -> Result<Self, Error> {
+        let (in_tx, in_rx) = mpsc::unbounded_channel();
+        let (out_tx, out_rx) = mpsc::unbounded_channel();
+        std::thread::spawn(move || SimWorker::create_and_run(in_rx, out_tx));
+        Ok(Self {
+            incoming: in_tx,
+            outgoing: out_rx,
+        })
+    }
+
+    /// The simulated images don't exist, so there is nothing to save.
+    pub async fn export_images(dir: &Path) -> Result<(), Error> {
+        info!("Simulating the export of the images to {}", dir.display());
+        Ok(())
+    }
+
+    /// The simulated images don't exist, so there is nothing to load.
+    pub async fn import_images(dir: &Path) -> Result<(), Error> {
+        info!("Simulating the import of the images from {}", dir.display());
+        Ok(())
+    }
+}
+
+pub struct SimWorker {
+    state: LaunchpadState,
+    in_rx: mpsc::UnboundedReceiver<Action>,
+    out_tx: mpsc::UnboundedSender<Reaction>,
+    tasks: Vec<SimTask>,
+    chain: SimChain,
+}
+
+impl SimWorker {
+    #[tokio::main]
+    async fn create_and_run(
+        in_rx: mpsc::UnboundedReceiver<Action>,
+        out_tx: mpsc::UnboundedSender<Reaction>,
+    ) -> Result<(), Error> {
+        let worker = SimWorker {
+            state: LaunchpadState::default(),
+            in_rx,
+            out_tx,
+            tasks: tasks::all(),
+            chain: SimChain::default(),
+        };
+        worker.entrypoint().await;
+        Ok(())
+    }
+
+    async fn entrypoint(mut self) {
+        self.load_configuration();
+        let added: Vec<_> = self
+            .tasks
+            .iter()
+            .map(|task| LaunchpadDelta::TaskAdded {
+                id: task.id.clone(),
+                state: TaskState::new(task.permanent),
+            })
+            .collect();
+        for delta in added {
+            self.apply_delta(delta);
+        }
+        let mut ticks = interval(TICK_INTERVAL);
+        loop {
+            select! {
+                action = self.in_rx.recv() => {
+                    if let Some(action) = action {
+                        self.process_incoming(action);
+                    } else {
+                        debug!("The bus is closed. Stopping the simulation.");
+                        break;
+                    }
+                }
+                _ = ticks.tick() => {
+                    self.process_tick();
+                }
+            }
+        }
+    }
+
+    /// The simulator never reads the settings from the disk and always starts with the defaults.
+    fn load_configuration(&mut self) {
+        let config = LaunchpadSettings::default();
+        self.apply_delta(LaunchpadDelta::UpdateConfig(config));
+    }
+
+    fn process_incoming(&mut self, incoming: Action) {
+        match incoming {
+            Action::Action(action) => self.process_action(action),
+            Action::Wallet(action) => self.process_wallet_action(action),
+        }
+    }
+
+    fn process_action(&mut self, action: LaunchpadAction) {
+        match action {
+            LaunchpadAction::Connect => {
+                let state = self.state.clone();
+                self.send(Reaction::State(state));
+            },
+            LaunchpadAction::ChangeSession(session) => {
+                self.apply_delta(LaunchpadDelta::UpdateSession(session));
+            },
+            LaunchpadAction::SaveSettings(saved_settings) => {
+                if let Some(mut settings) = self.state.config.settings.clone() {
+                    settings.saved_settings = saved_settings;
+                    self.apply_delta(LaunchpadDelta::UpdateConfig(settings));
+                }
+            },
+            LaunchpadAction::Shutdown => {
+                let mut session = self.state.config.session.clone();
+                session.stop_all();
+                self.apply_delta(LaunchpadDelta::UpdateSession(session));
+            },
+            LaunchpadAction::UpdateImage(id) => {
+                // The simulated images are always up to date
+                let delta = TaskDelta::UpdateAvailable(false);
+                self.apply_delta(LaunchpadDelta::TaskDelta { id, delta });
+            },
+            LaunchpadAction::PullImages { tasks } => {
+                // The simulated images are pulled instantly
+                let ids: Vec<_> = match tasks {
+                    Some(tasks) => tasks,
+                    None => self.state.containers.keys().cloned().collect(),
+                };
+                for id in ids {
+                    let progress = TaskProgress {
+                        pct: 100,
+                        ..TaskProgress::new("Pulled")
+                    };
+                    let delta = TaskDelta::PullProgress(progress);
+                    self.apply_delta(LaunchpadDelta::TaskDelta { id, delta });
+                }
+            },
+            LaunchpadAction::ExportImages(dir) | LaunchpadAction::ImportImages(dir) => {
+                // The simulated images don't exist
+                self.apply_delta(LaunchpadDelta::ImagesTransferred(dir));
+            },
+        }
+    }
+
+    fn process_wallet_action(&mut self, action: WalletAction) {
+        match action {
+            WalletAction::TransferFunds(funds) => {
+                let (deltas, result) = self.chain.transfer(funds);
+                for delta in deltas {
+                    self.apply_delta(delta);
+                }
+                self.send(Reaction::TransferResult(result));
+            },
+        }
+    }
+
+    fn process_tick(&mut self) {
+        let session = self.state.config.session.clone();
+        let mut deltas = Vec::new();
+        for task in &mut self.tasks {
+            let dependencies_ready = task.deps.iter().all(|dep| {
+                self.state
+                    .containers
+                    .get(dep)
+                    .map(|state| state.status.is_active())
+                    .unwrap_or_default()
+            });
+            for delta in task.step(&session, dependencies_ready) {
+                deltas.push(LaunchpadDelta::TaskDelta {
+                    id: task.id.clone(),
+                    delta,
+                });
+            }
+        }
+        deltas.extend(self.chain.step(&self.state));
+        for delta in deltas {
+            self.apply_delta(delta);
+        }
+    }
+
+    fn apply_delta(&mut self, delta: LaunchpadDelta) {
+        self.state.apply(delta.clone());
+        let reaction = Reaction::Delta(delta);
+        self.send(reaction);
+    }
+
+    fn send(&mut self, out: Reaction) {
+        if let Err(err) = self.out_tx.send(out) {
+            error!("Can't send an outgoing message: {}", err);
+        }
+    }
+}
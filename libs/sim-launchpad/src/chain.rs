@@ -0,0 +1,248 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::collections::VecDeque;
+
+use tari_launchpad_protocol::{
+    container::TaskId,
+    launchpad::{LaunchpadDelta, LaunchpadState},
+    node::{BaseNodeAddress, NodeDelta},
+    wallet::{MyIdentity, TransferFunds, TransferResult, WalletBalance, WalletDelta, WalletTransaction},
+};
+use tari_sdm::ids::ManagedTask;
+use tari_sdm_launchpad::resources::images;
+
+/// The height of the simulated chain when the node starts syncing.
+const INITIAL_HEIGHT: u64 = 10_000;
+/// How many blocks are loaded per tick while the node is syncing.
+const SYNC_STEP: u64 = 1_000;
+/// How many ticks the node spends on every sync stage.
+const SYNC_STAGE_TICKS: u64 = 3;
+/// A new block is found once in this number of ticks.
+const BLOCK_TICKS: u64 = 6;
+/// Every coinbase with an id divisible by this number gets mined. Others are cancelled.
+const MINED_EVERY: u64 = 3;
+const BLOCK_REWARD: u64 = 13_000_000_000;
+const PEER_COUNT: usize = 8;
+
+const NODE_PUBLIC_KEY: &str = "0eefb45a4de9484eca74846a4f47d2c8d38e76be1fec63b0112bd00d297c0928";
+const WALLET_ADDRESS: &str = "e2a1e6df5bbf5a8fe7ae8b8e7fd1cf0d82b6fa7e5b1e06fd5a1c14a7e7e4b5a3c6";
+const EMOJI_ID: &str = "🐢🌊🎲🍒🎉🚀🔑🌈🍀🎸🦀🌻";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CoinbaseStage {
+    Received,
+    Confirmed,
+}
+
+struct Coinbase {
+    tx_id: u64,
+    height: u64,
+    stage: CoinbaseStage,
+}
+
+/// Fabricates the state of the base node and the wallet. It syncs the chain, mines blocks if a miner is running and
+/// keeps the balance of the wallet.
+pub struct SimChain {
+    node_ticks: u64,
+    height: u64,
+    sync_status: String,
+    wallet_ready: bool,
+    next_tx_id: u64,
+    coinbases: VecDeque<Coinbase>,
+    available: u64,
+}
+
+impl Default for SimChain {
+    fn default() -> Self {
+        Self {
+            node_ticks: 0,
+            height: INITIAL_HEIGHT,
+            sync_status: String::new(),
+            wallet_ready: false,
+            next_tx_id: 1,
+            coinbases: VecDeque::new(),
+            available: 0,
+        }
+    }
+}
+
+impl SimChain {
+    /// Returns the changes of the node and the wallet caused by the tick.
+    pub fn step(&mut self, state: &LaunchpadState) -> Vec<LaunchpadDelta> {
+        let is_active = |id: TaskId| {
+            state
+                .containers
+                .get(&id)
+                .map(|task| task.status.is_active())
+                .unwrap_or_default()
+        };
+        let mut deltas = Vec::new();
+        if is_active(images::TariBaseNode::id()) {
+            self.step_node(&mut deltas);
+        } else if self.node_ticks > 0 {
+            self.node_ticks = 0;
+            self.sync_status.clear();
+            deltas.push(LaunchpadDelta::NodeDelta(NodeDelta::SetSyncStatus(
+                "Not Connected".into(),
+            )));
+            deltas.push(LaunchpadDelta::NodeDelta(NodeDelta::SetPeerCount(0)));
+        }
+        if is_active(images::TariWallet::id()) {
+            if !self.wallet_ready {
+                self.wallet_ready = true;
+                self.start_wallet(&mut deltas);
+            }
+            let is_mining = is_active(images::TariSha3Miner::id()) || is_active(images::XMRig::id());
+            if is_mining && self.is_synced() && self.node_ticks % BLOCK_TICKS == 0 {
+                self.mine_block(&mut deltas);
+            }
+        } else if self.wallet_ready {
+            self.wallet_ready = false;
+            deltas.push(LaunchpadDelta::WalletDelta(WalletDelta::SetActive(false)));
+        }
+        deltas
+    }
+
+    /// Spends funds if the wallet is running and has enough of them. The transaction is reported as the wallet
+    /// reports its events.
+    pub fn transfer(&mut self, funds: TransferFunds) -> (Vec<LaunchpadDelta>, TransferResult) {
+        let mut deltas = Vec::new();
+        let total = funds.amount.saturating_add(funds.fee);
+        let failure = if !self.wallet_ready {
+            Some("The wallet is not running")
+        } else if total > self.available {
+            Some("Insufficient funds")
+        } else {
+            None
+        };
+        let mut result = TransferResult {
+            address: funds.address,
+            tx_id: String::new(),
+            is_success: failure.is_none(),
+            failure_message: failure.unwrap_or_default().into(),
+        };
+        if result.is_success {
+            self.available -= total;
+            result.tx_id = self.next_tx_id().to_string();
+            let transaction = WalletTransaction {
+                event: "sent".into(),
+                tx_id: result.tx_id.clone(),
+                status: "Pending".into(),
+                direction: "Outbound".into(),
+                amount: funds.amount,
+                message: funds.message,
+                is_coinbase: false,
+            };
+            deltas.push(LaunchpadDelta::WalletDelta(WalletDelta::LogTransaction(transaction)));
+            deltas.push(self.balance());
+        }
+        (deltas, result)
+    }
+
+    fn is_synced(&self) -> bool {
+        self.node_ticks > SYNC_STAGE_TICKS * 3
+    }
+
+    fn step_node(&mut self, deltas: &mut Vec<LaunchpadDelta>) {
+        if self.node_ticks == 0 {
+            let identity = BaseNodeAddress {
+                public_key: NODE_PUBLIC_KEY.into(),
+                emoji_id: EMOJI_ID.into(),
+            };
+            deltas.push(LaunchpadDelta::NodeDelta(NodeDelta::SetIdentity(identity)));
+            deltas.push(LaunchpadDelta::NodeDelta(NodeDelta::SetPeerCount(PEER_COUNT)));
+        }
+        self.node_ticks += 1;
+        let sync_status = match self.node_ticks / SYNC_STAGE_TICKS {
+            0 => "Connecting",
+            1 => "Loading headers",
+            2 => "Loading Blocks",
+            _ => "Listening",
+        };
+        if sync_status == "Loading Blocks" {
+            self.height += SYNC_STEP;
+            deltas.push(LaunchpadDelta::NodeDelta(NodeDelta::SetChainLength(self.height)));
+        }
+        if self.sync_status != sync_status {
+            self.sync_status = sync_status.into();
+            deltas.push(LaunchpadDelta::NodeDelta(NodeDelta::SetSyncStatus(sync_status.into())));
+        }
+    }
+
+    fn start_wallet(&mut self, deltas: &mut Vec<LaunchpadDelta>) {
+        let identity = MyIdentity {
+            tari_address: WALLET_ADDRESS.into(),
+            emoji_id: EMOJI_ID.into(),
+        };
+        deltas.push(LaunchpadDelta::WalletDelta(WalletDelta::SetAddress(identity)));
+        deltas.push(LaunchpadDelta::WalletDelta(WalletDelta::SetActive(true)));
+        deltas.push(self.balance());
+    }
+
+    /// Adds a new block and moves the coinbases along the same lifecycle as the real wallet does:
+    /// `received` -> `confirmation` -> `mined`, or `received` -> `cancelled`.
+    fn mine_block(&mut self, deltas: &mut Vec<LaunchpadDelta>) {
+        self.height += 1;
+        deltas.push(LaunchpadDelta::NodeDelta(NodeDelta::SetChainLength(self.height)));
+        for mut coinbase in std::mem::take(&mut self.coinbases) {
+            let event = match coinbase.stage {
+                CoinbaseStage::Received if coinbase.tx_id % MINED_EVERY == 0 => {
+                    coinbase.stage = CoinbaseStage::Confirmed;
+                    let event = coinbase_event(&coinbase, "confirmation", "Mined Unconfirmed");
+                    self.coinbases.push_back(coinbase);
+                    event
+                },
+                CoinbaseStage::Received => coinbase_event(&coinbase, "cancelled", "Coinbase"),
+                CoinbaseStage::Confirmed => {
+                    self.available += BLOCK_REWARD;
+                    coinbase_event(&coinbase, "mined", "Mined Confirmed")
+                },
+            };
+            deltas.push(LaunchpadDelta::WalletDelta(WalletDelta::LogTransaction(event)));
+        }
+        let coinbase = Coinbase {
+            tx_id: self.next_tx_id(),
+            height: self.height + 1,
+            stage: CoinbaseStage::Received,
+        };
+        let transaction = coinbase_event(&coinbase, "received", "Coinbase");
+        deltas.push(LaunchpadDelta::WalletDelta(WalletDelta::LogTransaction(transaction)));
+        self.coinbases.push_back(coinbase);
+        deltas.push(self.balance());
+    }
+
+    fn balance(&self) -> LaunchpadDelta {
+        let pending_incoming: u64 = self
+            .coinbases
+            .iter()
+            .filter(|coinbase| coinbase.stage == CoinbaseStage::Confirmed)
+            .map(|_| BLOCK_REWARD)
+            .sum();
+        let balance = WalletBalance {
+            available: self.available.into(),
+            pending_incoming: pending_incoming.into(),
+            pending_outgoing: 0_u64.into(),
+            timelocked: 0_u64.into(),
+        };
+        LaunchpadDelta::WalletDelta(WalletDelta::UpdateBalance(balance))
+    }
+
+    fn next_tx_id(&mut self) -> u64 {
+        let tx_id = self.next_tx_id;
+        self.next_tx_id += 1;
+        tx_id
+    }
+}
+
+fn coinbase_event(coinbase: &Coinbase, event: &str, status: &str) -> WalletTransaction {
+    WalletTransaction {
+        event: event.into(),
+        tx_id: coinbase.tx_id.to_string(),
+        status: status.into(),
+        direction: "Inbound".into(),
+        amount: BLOCK_REWARD,
+        message: format!("Coinbase Transaction for Block #{}", coinbase.height),
+        is_coinbase: true,
+    }
+}
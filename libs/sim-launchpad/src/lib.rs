@@ -0,0 +1,11 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! A drop-in replacement of the `tari_sdm_launchpad` bus that doesn't touch Docker. It fabricates the lifecycle of
+//! the tasks, the base node sync and the mining rewards, so the UI and tests can be run on any machine.
+
+pub mod bus;
+mod chain;
+mod tasks;
+
+pub use bus::LaunchpadBus;
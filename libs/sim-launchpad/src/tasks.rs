@@ -0,0 +1,161 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use chrono::Local;
+use tari_launchpad_protocol::{
+    container::{LogLevel, LogRecord, StatsData, TaskDelta, TaskId, TaskProgress, TaskStatus},
+    session::LaunchpadSession,
+};
+use tari_sdm::ids::ManagedTask;
+use tari_sdm_launchpad::resources::{images, networks, volumes};
+
+/// The progress of the simulated pulling per tick.
+const PULL_STEP: u8 = 25;
+/// How often an active task writes a log record.
+const LOG_PERIOD: u64 = 10;
+const NANOS_PER_TICK: i64 = 1_000_000_000;
+const MEM_LIMIT: u64 = 4 * 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Inactive,
+    Pending,
+    Pulling(u8),
+    Starting,
+    Active,
+}
+
+impl Stage {
+    fn status(self) -> TaskStatus {
+        match self {
+            Self::Inactive => TaskStatus::Inactive,
+            Self::Pending => TaskStatus::Pending,
+            Self::Pulling(pct) => TaskStatus::Progress(TaskProgress {
+                pct,
+                stage: "Pulling the image".into(),
+                total: None,
+                eta: None,
+            }),
+            Self::Starting => TaskStatus::Progress(TaskProgress {
+                pct: 100,
+                stage: "Starting the container".into(),
+                total: None,
+                eta: None,
+            }),
+            Self::Active => TaskStatus::Active,
+        }
+    }
+}
+
+/// A fake task that walks through the same statuses as a real one.
+pub struct SimTask {
+    pub id: TaskId,
+    pub permanent: bool,
+    pub deps: Vec<TaskId>,
+    should_be_active: fn(&LaunchpadSession) -> bool,
+    /// The share of a CPU the task consumes, in percents.
+    load: i64,
+    stage: Stage,
+    uptime: u64,
+}
+
+impl SimTask {
+    fn new<T: ManagedTask>(permanent: bool, load: i64, should_be_active: fn(&LaunchpadSession) -> bool) -> Self {
+        Self {
+            id: T::id(),
+            permanent,
+            deps: T::deps(),
+            should_be_active,
+            load,
+            stage: Stage::Inactive,
+            uptime: 0,
+        }
+    }
+
+    fn image<T: ManagedTask>(load: i64, should_be_active: fn(&LaunchpadSession) -> bool) -> Self {
+        Self::new::<T>(false, load, should_be_active)
+    }
+
+    fn resource<T: ManagedTask>() -> Self {
+        Self::new::<T>(true, 0, |_| true)
+    }
+
+    /// Moves the task to the next stage and returns the changes to report.
+    pub fn step(&mut self, session: &LaunchpadSession, dependencies_ready: bool) -> Vec<TaskDelta> {
+        let mut deltas = Vec::new();
+        let next_stage = if (self.should_be_active)(session) {
+            match self.stage {
+                _ if self.permanent => Stage::Active,
+                Stage::Inactive | Stage::Pending if !dependencies_ready => Stage::Pending,
+                Stage::Inactive | Stage::Pending => Stage::Pulling(0),
+                Stage::Pulling(pct) if pct < 100 => Stage::Pulling(pct.saturating_add(PULL_STEP).min(100)),
+                Stage::Pulling(_) => Stage::Starting,
+                Stage::Starting | Stage::Active => Stage::Active,
+            }
+        } else {
+            Stage::Inactive
+        };
+        if next_stage != self.stage {
+            self.stage = next_stage;
+            deltas.push(TaskDelta::UpdateStatus(self.stage.status()));
+            match self.stage {
+                Stage::Active => deltas.push(self.log("The container is started")),
+                Stage::Inactive => {
+                    self.uptime = 0;
+                    deltas.push(self.log("The container is stopped"));
+                },
+                _ => {},
+            }
+        }
+        if self.stage == Stage::Active && !self.permanent {
+            self.uptime += 1;
+            deltas.push(TaskDelta::StatsRecord(self.stats()));
+            if self.uptime % LOG_PERIOD == 0 {
+                let message = format!("{} is running for {} ticks", self.id, self.uptime);
+                deltas.push(self.log(message));
+            }
+        }
+        deltas
+    }
+
+    fn log(&self, message: impl ToString) -> TaskDelta {
+        let record = LogRecord {
+            datetime: Local::now().naive_local(),
+            level: LogLevel::Info,
+            message: message.to_string(),
+        };
+        TaskDelta::LogRecord(record)
+    }
+
+    fn stats(&self) -> StatsData {
+        let ticks = self.uptime as i64;
+        // The load fluctuates a bit to make the charts alive
+        let load = self.load + ticks % 3;
+        let mem_usage = MEM_LIMIT / 100 * (self.load as u64 + self.uptime % 5);
+        StatsData {
+            timestamp: Local::now().naive_local(),
+            system_cpu_usage: ticks * NANOS_PER_TICK,
+            cpu_usage: ticks * NANOS_PER_TICK / 100 * load,
+            mem_limit: MEM_LIMIT.into(),
+            mem_usage: mem_usage.into(),
+        }
+    }
+}
+
+/// All the tasks that the real bus manages.
+pub fn all() -> Vec<SimTask> {
+    vec![
+        SimTask::resource::<networks::LocalNet>(),
+        SimTask::resource::<volumes::SharedVolume>(),
+        SimTask::resource::<volumes::SharedGrafanaVolume>(),
+        SimTask::image::<images::Tor>(2, LaunchpadSession::is_tor_active),
+        SimTask::image::<images::TariBaseNode>(15, LaunchpadSession::is_base_node_active),
+        SimTask::image::<images::TariWallet>(5, LaunchpadSession::is_wallet_active),
+        SimTask::image::<images::TariSha3Miner>(80, LaunchpadSession::is_sha3x_active),
+        SimTask::image::<images::Loki>(3, LaunchpadSession::is_loki_active),
+        SimTask::image::<images::Promtail>(2, LaunchpadSession::is_promtail_active),
+        SimTask::image::<images::Grafana>(3, LaunchpadSession::is_grafana_active),
+        SimTask::image::<images::MmProxy>(4, LaunchpadSession::is_mmproxy_active),
+        SimTask::image::<images::XMRig>(90, LaunchpadSession::is_xmrig_active),
+    ]
+}
@@ -0,0 +1,111 @@
+// Copyright 2023. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{convert::identity, time::Duration};
+
+use anyhow::Error;
+use tari_launchpad_protocol::{
+    container::TaskStatus,
+    launchpad::{Action, LaunchpadAction, LaunchpadState, Reaction},
+    wallet::{TransferFunds, WalletAction},
+};
+use tari_sdm::ids::{ManagedTask, TaskId};
+use tari_sdm_launchpad::resources::images;
+use tari_sim_launchpad::LaunchpadBus;
+use tokio::time::timeout;
+
+enum Status {
+    Init,
+    /// Waiting when the first block will be mined
+    WaitMining,
+    /// Waiting for the result of the transfer
+    WaitTransfer,
+    ContainersDeactivated,
+}
+
+#[tokio::test]
+async fn test_sim_mining() -> Result<(), Error> {
+    let mut bus = LaunchpadBus::start()?;
+    bus.incoming.send(Action::Action(LaunchpadAction::Connect))?;
+    let containers = vec![
+        images::Tor::id(),
+        images::TariBaseNode::id(),
+        images::TariWallet::id(),
+        images::TariSha3Miner::id(),
+    ];
+    let mut state: Option<LaunchpadState> = None;
+    let mut transfer = None;
+    let mut status = Status::Init;
+    loop {
+        let reaction = timeout(Duration::from_secs(120), bus.outgoing.recv())
+            .await?
+            .ok_or_else(|| Error::msg("Bus closed"))?;
+        match reaction {
+            Reaction::State(new_state) => {
+                state = Some(new_state);
+            },
+            Reaction::Delta(delta) => {
+                if let Some(state) = state.as_mut() {
+                    state.apply(delta);
+                }
+            },
+            Reaction::TransferResult(result) => {
+                transfer = Some(result);
+            },
+        }
+        let Some(state) = state.as_ref() else {
+            continue;
+        };
+        match status {
+            Status::Init => {
+                let mut session = state.config.session.clone();
+                session.sha3x_layer_active = true;
+                bus.incoming
+                    .send(Action::Action(LaunchpadAction::ChangeSession(session)))?;
+                status = Status::WaitMining;
+            },
+            Status::WaitMining => {
+                if state.wallet.session_confirmed_mined > 0 {
+                    assert!(check_containers(state, &containers, TaskStatus::is_active));
+                    let funds = TransferFunds {
+                        address: "recipient".into(),
+                        amount: 1_000_000,
+                        fee: 5,
+                        message: "Simulated transfer".into(),
+                    };
+                    bus.incoming.send(Action::Wallet(WalletAction::TransferFunds(funds)))?;
+                    status = Status::WaitTransfer;
+                }
+            },
+            Status::WaitTransfer => {
+                if let Some(result) = transfer.as_ref() {
+                    assert!(result.is_success, "{}", result.failure_message);
+                    let mut session = state.config.session.clone();
+                    session.sha3x_layer_active = false;
+                    bus.incoming
+                        .send(Action::Action(LaunchpadAction::ChangeSession(session)))?;
+                    status = Status::ContainersDeactivated;
+                }
+            },
+            Status::ContainersDeactivated => {
+                if check_containers(state, &containers, TaskStatus::is_inactive) && !state.wallet.active {
+                    return Ok(());
+                }
+            },
+        }
+    }
+}
+
+fn check_containers<F>(state: &LaunchpadState, ids: &[TaskId], func: F) -> bool
+where F: Fn(&TaskStatus) -> bool {
+    ids.iter()
+        .map(|id| {
+            state
+                .containers
+                .get(id)
+                .map(|container| &container.status)
+                .map(&func)
+                .unwrap_or_default()
+        })
+        .all(identity)
+}
@@ -82,9 +82,17 @@ impl Widget for WalletInfo {
     fn view_opt(&self, _ctx: &Context<Self>) -> Option<Html> {
         let remote_state = self.remote_state.get();
         let wallet = &remote_state.state.wallet;
+        let last_transfer = remote_state.last_transfer.as_ref().map(|result| {
+            if result.is_success {
+                format!("Sent to {}, transaction {}", result.address, result.tx_id)
+            } else {
+                format!("Can't send to {}: {}", result.address, result.failure_message)
+            }
+        });
         Some(html! {
             <div>
             { format!("{:?}", wallet) }
+            { for last_transfer.map(|text| html! { <div>{ text }</div> }) }
             </div>
         })
     }
@@ -21,7 +21,10 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use tari_launchpad_protocol::launchpad::{LaunchpadState, Reaction};
+use tari_launchpad_protocol::{
+    launchpad::{LaunchpadState, Reaction},
+    wallet::TransferResult,
+};
 
 use crate::widget::{SharedState, State};
 
@@ -31,6 +34,8 @@ pub static REMOTE_STATE: SharedState<RemoteState> = SharedState::new();
 pub struct RemoteState {
     pub state: LaunchpadState,
     pub loaded: bool,
+    /// The outcome of the last transfer of funds.
+    pub last_transfer: Option<TransferResult>,
 }
 
 impl State for RemoteState {
@@ -44,6 +49,9 @@ impl State for RemoteState {
             Reaction::Delta(delta) => {
                 self.state.apply(delta);
             },
+            Reaction::TransferResult(result) => {
+                self.last_transfer = Some(result);
+            },
         }
     }
 }